@@ -0,0 +1,24 @@
+//! 启用 `embed-data` 特性时，在编译期校验将被嵌入的 `phone.dat`
+
+#[path = "src/layout.rs"]
+#[allow(dead_code)]
+mod layout;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=src/layout.rs");
+    if std::env::var_os("CARGO_FEATURE_EMBED_DATA").is_none() {
+        return;
+    }
+
+    println!("cargo:rerun-if-changed=phone.dat");
+    let path =
+        std::path::Path::new(&std::env::var("CARGO_MANIFEST_DIR").unwrap()).join("phone.dat");
+    let bytes = match std::fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(e) => panic!("无法读取待嵌入的数据文件 {}: {}", path.display(), e),
+    };
+    if let Err(e) = layout::check_layout(&bytes) {
+        panic!("待嵌入的数据文件 {} 已损坏: {}", path.display(), e);
+    }
+}
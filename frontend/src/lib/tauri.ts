@@ -7,6 +7,7 @@ export interface PhoneInfo {
   zip_code: string  // 后端是zip_code，不是zip
   area_code: string
   card_type: string
+  icon_key?: string  // 运营商图标标识，如 cmcc、cucc_v
 }
 
 // 单个查询结果类型
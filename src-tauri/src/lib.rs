@@ -41,6 +41,16 @@ pub fn run() {
             
             // 将 phone_data 存储到应用状态中
             app.manage(phone_data);
+
+            // 日志配置决定查询日志中的号码是否脱敏，读取失败时使用默认配置（脱敏）
+            let logging = match phone_lookup_rs::config::Config::load() {
+                Ok(config) => config.logging,
+                Err(e) => {
+                    eprintln!("加载配置失败，使用默认日志配置: {}", e);
+                    phone_lookup_rs::config::LoggingConfig::default()
+                }
+            };
+            app.manage(logging);
             
             // 在开发模式下启用日志插件
             #[cfg(debug_assertions)]
@@ -57,6 +67,7 @@ pub fn run() {
             phone_lookup_rs::tauri_commands::query_phone,
             phone_lookup_rs::tauri_commands::query_phones_batch,
             phone_lookup_rs::tauri_commands::get_app_info,
+            phone_lookup_rs::tauri_commands::get_cache_stats,
             phone_lookup_rs::tauri_commands::clear_cache,
             phone_lookup_rs::tauri_commands::set_cache_size
         ])
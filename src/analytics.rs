@@ -0,0 +1,240 @@
+//! 全库统计分析
+//!
+//! 这里的方法需要扫描整个索引，耗时远高于单次查询，
+//! 在异步服务中应放到阻塞线程池执行。
+
+use std::collections::HashMap;
+
+use crate::{CardType, PhoneData, Snapshot};
+
+/// 每个 7 位号段包含的完整号码数（后 4 位）
+pub const NUMBERS_PER_PREFIX: u64 = 10_000;
+
+/// 各运营商的号段数量，按数量从多到少排列
+pub type CarrierBreakdown = Vec<(CardType, usize)>;
+
+/// 计入运营商统计的运营商，代码无效或运营商未知时返回 `None`
+fn known_carrier(code: u8) -> Option<CardType> {
+    CardType::from_u8(code)
+        .ok()
+        .filter(|&card_type| card_type != CardType::Unknown)
+}
+
+impl PhoneData {
+    /// 估算数据库覆盖的完整号码总数
+    ///
+    /// 按每个号段 10000 个号码计算，是上限估计：号段内并非所有后缀都已放号。
+    pub fn estimated_number_capacity(&self) -> u64 {
+        self.index_count() as u64 * NUMBERS_PER_PREFIX
+    }
+
+    /// 统计某个省份各运营商的号段数量，按数量从多到少排列
+    ///
+    /// 首次查询某省份时扫描全库，结果按省份缓存。运营商代码无效或为
+    /// [`CardType::Unknown`] 的号段不计入统计，省份不存在时返回空列表。
+    pub fn province_carrier_breakdown(&self, province: &str) -> CarrierBreakdown {
+        let data = self.snapshot();
+        if let Some(breakdown) = data.carrier_breakdowns.read().unwrap().get(province) {
+            return breakdown.clone();
+        }
+
+        let mut matches: HashMap<i32, bool> = HashMap::new();
+        let mut counts: HashMap<CardType, usize> = HashMap::new();
+        for index_item in data.index.iter() {
+            let in_province = *matches.entry(index_item.records_offset).or_insert_with(|| {
+                self.parse_to_record(&data, index_item.records_offset as usize)
+                    .is_ok_and(|record| record.province == province)
+            });
+            if !in_province {
+                continue;
+            }
+            if let Some(card_type) = known_carrier(index_item.card_type) {
+                *counts.entry(card_type).or_insert(0) += 1;
+            }
+        }
+
+        let mut breakdown: CarrierBreakdown = counts.into_iter().collect();
+        breakdown.sort_by_key(|&(card_type, count)| (std::cmp::Reverse(count), card_type as u8));
+        data.carrier_breakdowns
+            .write()
+            .unwrap()
+            .insert(province.to_string(), breakdown.clone());
+        breakdown
+    }
+
+    /// 统计每种运营商的号段数量
+    ///
+    /// 首次调用时扫描全部索引，结果随当前数据快照缓存。
+    /// 运营商代码无效或为 [`CardType::Unknown`] 的号段不计入统计。
+    pub fn carrier_distribution(&self) -> HashMap<CardType, usize> {
+        let data = self.snapshot();
+        data.carrier_distribution
+            .get_or_init(|| {
+                let mut distribution = HashMap::new();
+                for index_item in data.index.iter() {
+                    if let Some(card_type) = known_carrier(index_item.card_type) {
+                        *distribution.entry(card_type).or_insert(0) += 1;
+                    }
+                }
+                distribution
+            })
+            .clone()
+    }
+
+    /// 统计每个省份的号段数量
+    ///
+    /// 记录无法解析的号段不计入统计。首次调用时扫描全部索引，每个记录偏移只解析一次，
+    /// 结果随当前数据快照缓存。
+    pub fn province_distribution(&self) -> HashMap<String, usize> {
+        let data = self.snapshot();
+        data.province_distribution
+            .get_or_init(|| self.build_province_distribution(&data))
+            .clone()
+    }
+
+    fn build_province_distribution(&self, data: &Snapshot) -> HashMap<String, usize> {
+        let mut provinces: HashMap<i32, Option<String>> = HashMap::new();
+        let mut distribution = HashMap::new();
+        for index_item in data.index.iter() {
+            let province = provinces
+                .entry(index_item.records_offset)
+                .or_insert_with(|| {
+                    self.parse_to_record(data, index_item.records_offset as usize)
+                        .ok()
+                        .map(|record| record.province)
+                });
+            if let Some(province) = province {
+                *distribution.entry(province.clone()).or_insert(0) += 1;
+            }
+        }
+        distribution
+    }
+
+    /// 指定省份的号段数量，省份不存在时为 0
+    ///
+    /// 与 [`PhoneData::prefixes_by_province`] 共用反向索引，首次调用之后不再扫描全部索引。
+    pub fn segment_count_for_province(&self, province: &str) -> usize {
+        let data = self.snapshot();
+        data.province_prefixes
+            .get_or_init(|| self.build_province_prefixes(&data))
+            .get(province)
+            .map_or(0, Vec::len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::known_carrier;
+    use crate::tests::{create_mock_phone_data, create_mock_phone_data_with};
+    use crate::CardType;
+
+    #[test]
+    fn test_estimated_number_capacity() {
+        let phone_data = create_mock_phone_data();
+        assert_eq!(phone_data.estimated_number_capacity(), 0);
+
+        let phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380014, "北京|北京|100000|010", 2),
+        ]);
+        assert_eq!(
+            phone_data.estimated_number_capacity(),
+            phone_data.index_count() as u64 * 10_000
+        );
+    }
+
+    #[test]
+    fn test_province_carrier_breakdown() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1340755, "广东|深圳|518000|0755", 1),
+            (1340756, "广东|广州|510000|020", 1),
+            (1340757, "广东|佛山|528000|0757", 1),
+            (1380013, "北京|北京|100000|010", 3),
+            (1560755, "广东|深圳|518000|0755", 2),
+            (1560756, "广东|广州|510000|020", 2),
+            (1890755, "广东|深圳|518000|0755", 3),
+            (1890756, "广东|深圳|518000|0755", 0),
+            (1890757, "广东|深圳|518000|0755", 9),
+        ]);
+
+        let expected = vec![
+            (CardType::Cmcc, 3),
+            (CardType::Cucc, 2),
+            (CardType::Ctcc, 1),
+        ];
+        assert_eq!(phone_data.province_carrier_breakdown("广东"), expected);
+        // 第二次查询命中缓存
+        assert!(phone_data
+            .snapshot()
+            .carrier_breakdowns
+            .read()
+            .unwrap()
+            .contains_key("广东"));
+        assert_eq!(phone_data.province_carrier_breakdown("广东"), expected);
+
+        assert_eq!(
+            phone_data.province_carrier_breakdown("北京"),
+            vec![(CardType::Ctcc, 1)]
+        );
+        assert!(phone_data.province_carrier_breakdown("西藏").is_empty());
+    }
+
+    #[test]
+    fn test_province_distribution() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380014, "北京|北京|100000|010", 2),
+            (1380755, "广东|深圳|518000|0755", 1),
+            (1380756, "广东|广州|510000|020", 3),
+            (1380757, "广东|佛山|528000|0757", 3),
+        ]);
+
+        let distribution = phone_data.province_distribution();
+        assert_eq!(distribution.len(), 2);
+        assert_eq!(distribution["北京"], 2);
+        assert_eq!(distribution["广东"], 3);
+
+        for (province, count) in &distribution {
+            assert_eq!(phone_data.segment_count_for_province(province), *count);
+        }
+        assert_eq!(phone_data.segment_count_for_province("火星"), 0);
+    }
+
+    #[test]
+    fn test_carrier_distribution() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1340755, "广东|深圳|518000|0755", 1),
+            (1340756, "广东|广州|510000|020", 1),
+            (1380013, "北京|北京|100000|010", 3),
+            (1560755, "广东|深圳|518000|0755", 2),
+            (1700755, "广东|深圳|518000|0755", 5),
+            (1700756, "广东|广州|510000|020", 0),
+        ]);
+        let distribution = phone_data.carrier_distribution();
+        assert_eq!(distribution[&CardType::Cmcc], 2);
+        assert_eq!(distribution[&CardType::CuccV], 1);
+        assert!(!distribution.contains_key(&CardType::Cbcc));
+        assert!(!distribution.contains_key(&CardType::Unknown));
+        assert_eq!(
+            distribution.values().sum::<usize>(),
+            phone_data.index_count() - 1
+        );
+        assert!(phone_data.snapshot().carrier_distribution.get().is_some());
+
+        let phone_data = crate::PhoneData::new().unwrap();
+        let unknown = phone_data
+            .snapshot()
+            .index
+            .iter()
+            .filter(|item| known_carrier(item.card_type).is_none())
+            .count();
+        assert_eq!(
+            phone_data.carrier_distribution().values().sum::<usize>() + unknown,
+            phone_data.index_count()
+        );
+        assert_eq!(
+            phone_data.province_distribution().values().sum::<usize>(),
+            phone_data.index_count()
+        );
+    }
+}
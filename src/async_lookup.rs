@@ -0,0 +1,117 @@
+//! 异步查询接口
+//!
+//! 单个号码的查询是一次内存中的二分查找加记录解析，耗时在微秒级，不会阻塞运行时，
+//! 因此 [`PhoneData::find_async`] 直接返回已就绪的结果，不做线程切换。
+//!
+//! 批量查询则不同。`futures::stream::iter(..).buffered(n)` 的并发只发生在单个任务内部：
+//! 每个查询 future 没有任何等待点，会被依次轮询完成，所有查询实际上在同一个工作线程上
+//! 串行执行，还要额外承担每个号码一个 future 的开销。[`PhoneData::find_batch_async`]
+//! 把号码切成与 CPU 核数相当的若干块，每块交给 `tokio::task::spawn_blocking`，
+//! 查询在阻塞线程池上并行执行，既能利用多核，也不会长时间占用异步工作线程。
+
+use std::sync::Arc;
+
+use crate::{Fallible, PhoneData, PhoneNoInfo};
+
+/// 每个阻塞任务至少处理的号码数，号码较少时线程切换的开销会超过并行的收益
+const MIN_NUMBERS_PER_TASK: usize = 256;
+
+impl PhoneData {
+    /// 异步查询单个号码，结果与 [`PhoneData::find`] 相同
+    ///
+    /// 查询是纯 CPU 计算且耗时很短，返回的 future 首次轮询即完成。
+    pub async fn find_async(&self, no: &str) -> Fallible<PhoneNoInfo> {
+        self.find(no)
+    }
+
+    /// 在阻塞线程池上并行批量查询，结果顺序与输入一一对应
+    ///
+    /// 号码不超过 256 个时直接在当前任务中查询。
+    /// 必须在 tokio 运行时中调用；查询过程中的 panic 会在调用方重新抛出。
+    pub async fn find_batch_async(
+        self: &Arc<Self>,
+        numbers: Vec<String>,
+    ) -> Vec<Fallible<PhoneNoInfo>> {
+        if numbers.len() <= MIN_NUMBERS_PER_TASK {
+            return numbers.iter().map(|no| self.find(no)).collect();
+        }
+
+        let workers = num_cpus::get().max(1);
+        let chunk_size = numbers.len().div_ceil(workers).max(MIN_NUMBERS_PER_TASK);
+        let mut numbers = numbers;
+        let mut chunks = Vec::with_capacity(workers);
+        while numbers.len() > chunk_size {
+            let rest = numbers.split_off(chunk_size);
+            chunks.push(std::mem::replace(&mut numbers, rest));
+        }
+        chunks.push(numbers);
+
+        let tasks: Vec<_> = chunks
+            .into_iter()
+            .map(|chunk| {
+                let phone_data = Arc::clone(self);
+                tokio::task::spawn_blocking(move || {
+                    chunk
+                        .iter()
+                        .map(|no| phone_data.find(no))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        let mut results = Vec::new();
+        for task in tasks {
+            match task.await {
+                Ok(chunk) => results.extend(chunk),
+                Err(e) => std::panic::resume_unwind(e.into_panic()),
+            }
+        }
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::create_mock_phone_data_with;
+
+    fn phone_data() -> PhoneData {
+        create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380755, "广东|深圳|518000|0755", 3),
+        ])
+    }
+
+    #[tokio::test]
+    async fn test_find_async_matches_find() {
+        let phone_data = phone_data();
+        assert_eq!(
+            phone_data.find_async("13800138000").await.unwrap(),
+            phone_data.find("13800138000").unwrap()
+        );
+        assert!(phone_data.find_async("123").await.is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_find_batch_async_keeps_order() {
+        let phone_data = Arc::new(phone_data());
+        let numbers: Vec<String> = (0..2000)
+            .map(|i| match i % 4 {
+                0 => "13800138000",
+                1 => "13807550000",
+                2 => "13900000000",
+                _ => "12",
+            })
+            .map(str::to_string)
+            .collect();
+
+        let results = phone_data.find_batch_async(numbers.clone()).await;
+        assert_eq!(results.len(), numbers.len());
+        for (no, result) in numbers.iter().zip(&results) {
+            match phone_data.find(no) {
+                Ok(info) => assert_eq!(result.as_ref().unwrap(), &info),
+                Err(_) => assert!(result.is_err()),
+            }
+        }
+    }
+}
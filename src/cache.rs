@@ -0,0 +1,411 @@
+//! 查询结果缓存
+//!
+//! 以号码字符串为键的缓存：条目存放在槽位数组中，串成一条双向链表，条目数超过容量时按
+//! [`EvictionPolicy`] 选出被淘汰的条目。
+//!
+//! - LRU：命中时把条目移到链表头，从链表尾淘汰
+//! - FIFO：链表保持插入顺序，从链表尾淘汰
+//! - LFU：链表保持插入顺序，命中时只增加原子计数，淘汰时扫描出命中次数最少的条目，
+//!   次数相同时淘汰最早插入的；淘汰需要遍历全部条目，适合中小容量的缓存
+//!
+//! 设置了 TTL 时，插入超过 TTL 的条目视为不存在，再次写入时刷新；需要淘汰时先移除全部过期条目，
+//! 仍超出容量才按策略淘汰。
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::{EvictionPolicy, PhoneNoInfo};
+
+/// 链表中"无节点"的占位下标
+const NIL: usize = usize::MAX;
+
+/// 单个缓存条目，同时是链表的节点
+#[derive(Debug)]
+struct CacheEntry {
+    key: String,
+    info: PhoneNoInfo,
+    /// 命中次数，LFU 策略在读锁下更新
+    hits: AtomicU64,
+    /// 写入（或刷新）时间，用于 TTL 判断
+    inserted: Instant,
+    /// 靠近链表头的相邻条目
+    prev: usize,
+    /// 靠近链表尾的相邻条目
+    next: usize,
+}
+
+/// 以号码字符串为键的查询结果缓存
+#[derive(Debug)]
+pub(crate) struct ResultCache {
+    /// 号码 -> 槽位下标
+    map: HashMap<String, usize>,
+    /// 条目槽位，被移除的槽位置为 `None` 并记入 `free`
+    slots: Vec<Option<CacheEntry>>,
+    free: Vec<usize>,
+    /// 链表头：LRU 下为最近使用的条目，其余策略下为最新插入的条目
+    head: usize,
+    /// 链表尾
+    tail: usize,
+    /// 最大条目数
+    capacity: usize,
+    policy: EvictionPolicy,
+    /// 条目的存活时间，`None` 表示永不过期
+    ttl: Option<Duration>,
+}
+
+impl ResultCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self::with_policy(capacity, EvictionPolicy::default())
+    }
+
+    pub(crate) fn with_policy(capacity: usize, policy: EvictionPolicy) -> Self {
+        Self {
+            map: HashMap::new(),
+            slots: Vec::new(),
+            free: Vec::new(),
+            head: NIL,
+            tail: NIL,
+            capacity,
+            policy,
+            ttl: None,
+        }
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub(crate) fn policy(&self) -> EvictionPolicy {
+        self.policy
+    }
+
+    pub(crate) fn ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
+
+    /// 设置条目的存活时间，对已有条目同样生效
+    pub(crate) fn set_ttl(&mut self, ttl: Option<Duration>) {
+        self.ttl = ttl;
+    }
+
+    fn is_expired(&self, entry: &CacheEntry) -> bool {
+        self.ttl.is_some_and(|ttl| entry.inserted.elapsed() >= ttl)
+    }
+
+    /// 调整最大条目数，超出新容量的条目按淘汰策略移除，返回淘汰的条目数
+    pub(crate) fn set_capacity(&mut self, capacity: usize) -> usize {
+        self.capacity = capacity;
+        self.trim_to(capacity)
+    }
+
+    fn entry(&self, slot: usize) -> &CacheEntry {
+        self.slots[slot].as_ref().expect("cache slot in use")
+    }
+
+    fn entry_mut(&mut self, slot: usize) -> &mut CacheEntry {
+        self.slots[slot].as_mut().expect("cache slot in use")
+    }
+
+    /// 读取条目并记录一次命中，同时返回是否还需要调用 [`Self::touch`] 调整顺序
+    ///
+    /// 只需共享引用，调用方可在读锁下完成命中判断，仅在需要调整顺序时再取写锁。
+    /// 只有 LRU 策略、且条目不在链表头时才需要调整。
+    pub(crate) fn peek(&self, key: &str) -> Option<(&PhoneNoInfo, bool)> {
+        let slot = *self.map.get(key)?;
+        let entry = self.entry(slot);
+        if self.is_expired(entry) {
+            return None;
+        }
+        entry.hits.fetch_add(1, Ordering::Relaxed);
+        let needs_touch = self.policy == EvictionPolicy::Lru && slot != self.head;
+        Some((&entry.info, needs_touch))
+    }
+
+    /// LRU 策略下把条目移到链表头，其余策略或条目不存在时不做任何事
+    pub(crate) fn touch(&mut self, key: &str) {
+        if self.policy != EvictionPolicy::Lru {
+            return;
+        }
+        if let Some(&slot) = self.map.get(key) {
+            if slot != self.head {
+                self.unlink(slot);
+                self.push_front(slot);
+            }
+        }
+    }
+
+    /// 是否存在未过期的条目
+    pub(crate) fn contains_key(&self, key: &str) -> bool {
+        self.map
+            .get(key)
+            .is_some_and(|&slot| !self.is_expired(self.entry(slot)))
+    }
+
+    /// 插入或覆盖条目，超出容量时按淘汰策略移除条目，返回淘汰的条目数
+    pub(crate) fn insert(&mut self, key: String, info: PhoneNoInfo) -> usize {
+        if let Some(&slot) = self.map.get(&key) {
+            let entry = self.entry_mut(slot);
+            entry.info = info;
+            entry.inserted = Instant::now();
+            self.touch(&key);
+            return 0;
+        }
+        let entry = CacheEntry {
+            key: key.clone(),
+            info,
+            hits: AtomicU64::new(0),
+            inserted: Instant::now(),
+            prev: NIL,
+            next: NIL,
+        };
+        let slot = match self.free.pop() {
+            Some(slot) => {
+                self.slots[slot] = Some(entry);
+                slot
+            }
+            None => {
+                self.slots.push(Some(entry));
+                self.slots.len() - 1
+            }
+        };
+        self.map.insert(key, slot);
+        self.push_front(slot);
+        // 新条目不参与本次淘汰，否则 LFU 下命中次数为 0 的新条目总会被立即移除
+        let mut evicted = 0;
+        if self.map.len() > self.capacity {
+            evicted += self.purge_expired();
+        }
+        while self.map.len() > self.capacity {
+            let keep = if self.map.len() > 1 { slot } else { NIL };
+            self.evict(keep);
+            evicted += 1;
+        }
+        evicted
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        let ttl = self.ttl;
+        *self = Self::with_policy(self.capacity, self.policy);
+        self.ttl = ttl;
+    }
+
+    /// 移除全部过期条目，返回移除的条目数
+    pub(crate) fn purge_expired(&mut self) -> usize {
+        if self.ttl.is_none() {
+            return 0;
+        }
+        let expired: Vec<usize> = (0..self.slots.len())
+            .filter(|&slot| {
+                self.slots[slot]
+                    .as_ref()
+                    .is_some_and(|entry| self.is_expired(entry))
+            })
+            .collect();
+        for &slot in &expired {
+            self.remove_slot(slot);
+        }
+        expired.len()
+    }
+
+    /// 先移除过期条目，再按淘汰策略移除条目，直到条目数不超过 `target`，返回移除的条目数
+    /// （不修改容量）
+    pub(crate) fn trim_to(&mut self, target: usize) -> usize {
+        let mut evicted = 0;
+        if self.map.len() > target {
+            evicted += self.purge_expired();
+        }
+        while self.map.len() > target {
+            self.evict(NIL);
+            evicted += 1;
+        }
+        evicted
+    }
+
+    /// 按淘汰策略移除一个条目，`keep` 指定的槽位不会被选中
+    fn evict(&mut self, keep: usize) {
+        let slot = match self.policy {
+            EvictionPolicy::Lru | EvictionPolicy::Fifo => {
+                if self.tail == keep {
+                    self.entry(keep).prev
+                } else {
+                    self.tail
+                }
+            }
+            EvictionPolicy::Lfu => self.least_frequently_used(keep),
+        };
+        self.remove_slot(slot);
+    }
+
+    fn remove_slot(&mut self, slot: usize) {
+        self.unlink(slot);
+        let entry = self.slots[slot].take().expect("cache slot in use");
+        self.map.remove(&entry.key);
+        self.free.push(slot);
+    }
+
+    /// 从链表尾（最早插入）向头扫描，返回命中次数最少的条目
+    fn least_frequently_used(&self, keep: usize) -> usize {
+        let mut victim = NIL;
+        let mut victim_hits = u64::MAX;
+        let mut slot = self.tail;
+        while slot != NIL {
+            let entry = self.entry(slot);
+            let hits = entry.hits.load(Ordering::Relaxed);
+            if slot != keep && hits < victim_hits {
+                victim = slot;
+                victim_hits = hits;
+            }
+            slot = entry.prev;
+        }
+        victim
+    }
+
+    fn unlink(&mut self, slot: usize) {
+        let (prev, next) = {
+            let entry = self.entry(slot);
+            (entry.prev, entry.next)
+        };
+        if prev == NIL {
+            self.head = next;
+        } else {
+            self.entry_mut(prev).next = next;
+        }
+        if next == NIL {
+            self.tail = prev;
+        } else {
+            self.entry_mut(next).prev = prev;
+        }
+    }
+
+    fn push_front(&mut self, slot: usize) {
+        let old_head = self.head;
+        {
+            let entry = self.entry_mut(slot);
+            entry.prev = NIL;
+            entry.next = old_head;
+        }
+        if old_head == NIL {
+            self.tail = slot;
+        } else {
+            self.entry_mut(old_head).prev = slot;
+        }
+        self.head = slot;
+    }
+
+    /// 估算缓存占用的堆内存字节数（键、结果字符串与条目本身，不含哈希表的空槽）
+    pub(crate) fn estimated_bytes(&self) -> usize {
+        self.slots
+            .iter()
+            .flatten()
+            .map(|entry| {
+                let info = &entry.info;
+                std::mem::size_of::<(String, usize)>()
+                    + std::mem::size_of::<Option<CacheEntry>>()
+                    + 2 * entry.key.capacity()
+                    + info.province.capacity()
+                    + info.city.capacity()
+                    + info.zip_code.capacity()
+                    + info.area_code.capacity()
+                    + info.card_type.capacity()
+                    + info.icon_key.capacity()
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info() -> PhoneNoInfo {
+        PhoneNoInfo {
+            province: "北京".to_string(),
+            city: "北京".to_string(),
+            zip_code: "100000".to_string(),
+            area_code: "010".to_string(),
+            card_type: "中国移动".to_string(),
+            icon_key: "cmcc".to_string(),
+            #[cfg(feature = "timezone")]
+            timezone: crate::TIMEZONE.to_string(),
+        }
+    }
+
+    /// 依次插入 a、b、c，再按 `hits` 的顺序读取（需要时调整顺序），最后插入 d
+    fn run(policy: EvictionPolicy, hits: &[&str]) -> ResultCache {
+        let mut cache = ResultCache::with_policy(3, policy);
+        for key in ["a", "b", "c"] {
+            cache.insert(key.to_string(), info());
+        }
+        for key in hits {
+            let (_, needs_touch) = cache.peek(key).unwrap();
+            if needs_touch {
+                cache.touch(key);
+            }
+        }
+        assert_eq!(cache.insert("d".to_string(), info()), 1);
+        assert_eq!(cache.len(), 3);
+        assert!(cache.contains_key("d"));
+        cache
+    }
+
+    #[test]
+    fn test_lru_evicts_least_recently_used() {
+        let cache = run(EvictionPolicy::Lru, &["a", "b", "a"]);
+        assert!(!cache.contains_key("c"));
+    }
+
+    #[test]
+    fn test_fifo_evicts_oldest_insert() {
+        let cache = run(EvictionPolicy::Fifo, &["a", "a", "b"]);
+        assert!(!cache.contains_key("a"));
+    }
+
+    #[test]
+    fn test_lfu_evicts_least_frequently_used() {
+        let cache = run(EvictionPolicy::Lfu, &["a", "a", "c", "c", "b"]);
+        assert!(!cache.contains_key("b"));
+
+        // 次数相同时淘汰最早插入的
+        let cache = run(EvictionPolicy::Lfu, &["a", "b", "c"]);
+        assert!(!cache.contains_key("a"));
+    }
+
+    #[test]
+    fn test_trim_follows_policy() {
+        let mut cache = run(EvictionPolicy::Lfu, &["a", "a", "c", "c", "b"]);
+        cache.peek("d").unwrap();
+        // d 命中 1 次先被淘汰，a 与 c 同为 2 次时淘汰更早插入的 a
+        assert_eq!(cache.trim_to(1), 2);
+        assert!(cache.contains_key("c"));
+        cache.clear();
+        assert_eq!(cache.policy(), EvictionPolicy::Lfu);
+    }
+
+    #[test]
+    fn test_ttl_expires_and_purges_first() {
+        let mut cache = ResultCache::with_policy(3, EvictionPolicy::Lru);
+        cache.set_ttl(Some(Duration::from_millis(30)));
+        cache.insert("a".to_string(), info());
+        cache.insert("b".to_string(), info());
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(cache.peek("a").is_none());
+        assert!(!cache.contains_key("b"));
+
+        // 刷新 a 后只有 b 过期，超出容量时先移除 b 而不是按 LRU 淘汰
+        cache.insert("a".to_string(), info());
+        cache.insert("c".to_string(), info());
+        assert_eq!(cache.insert("d".to_string(), info()), 1);
+        assert_eq!(cache.len(), 3);
+        assert!(cache.contains_key("a"));
+        assert!(cache.contains_key("c"));
+        assert!(cache.contains_key("d"));
+
+        cache.clear();
+        assert_eq!(cache.ttl(), Some(Duration::from_millis(30)));
+    }
+}
@@ -0,0 +1,293 @@
+//! 紧凑数据格式
+//!
+//! `phone.dat` 中每条记录是完整的 `省|市|邮编|区号` 字符串，省份、城市等字段在记录之间大量重复，
+//! 索引项也以定长 9 字节存储。紧凑格式把字段拆开驻留，并对索引做差分变长编码，
+//! 文件体积约为原格式的一半。
+//!
+//! # 布局
+//!
+//! 所有整数均为小端序，`varint` 为 LEB128 无符号变长整数，`zigzag` 为 zigzag 编码后的 `varint`。
+//!
+//! | 字段 | 类型 | 说明 |
+//! |------|------|------|
+//! | 魔数 | `[u8; 4]` | 固定为 `PLRC` |
+//! | 格式版本 | `u8` | 当前为 [`COMPACT_FORMAT_VERSION`] |
+//! | 数据版本 | `[u8; 4]` | 原数据库的版本号，如 `2502` |
+//! | 字符串数 | `varint` | 之后为每个字符串的 `varint` 字节长度和 UTF-8 内容 |
+//! | 记录数 | `varint` | 之后为每条记录的 4 个字符串编号（`varint`）：省、市、邮编、区号 |
+//! | 索引数 | `varint` | 之后为每个索引项：与上一项号段的差值（`zigzag`）、记录编号（`varint`）、运营商（`u8`） |
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+use crate::{ErrorKind, Fallible, PhoneData, DEFAULT_RECORD_DELIMITER};
+
+/// 紧凑格式魔数
+pub const COMPACT_MAGIC: [u8; 4] = *b"PLRC";
+
+/// 紧凑格式版本，原始 `phone.dat` 视为版本 1
+pub const COMPACT_FORMAT_VERSION: u8 = 2;
+
+impl PhoneData {
+    /// 以紧凑格式写出数据库，格式见 [`crate::compact`]
+    ///
+    /// 记录无法解析或索引项引用了不存在的记录时返回 [`ErrorKind::InvalidPhoneDatabase`]。
+    pub fn write_compact<W: Write>(&self, w: &mut W) -> Fallible<()> {
+        let mut strings: Vec<&str> = Vec::new();
+        let mut string_ids: HashMap<String, usize> = HashMap::new();
+        let mut records: Vec<[usize; 4]> = Vec::new();
+        let mut record_ids: HashMap<i32, usize> = HashMap::new();
+        let data = self.snapshot();
+        let mut index = Vec::with_capacity(data.index.len());
+
+        // 先解析全部记录，字符串在写出前统一驻留
+        let mut parsed = Vec::new();
+        for index_item in data.index.iter() {
+            let next_id = record_ids.len();
+            let record_id = *record_ids
+                .entry(index_item.records_offset)
+                .or_insert(next_id);
+            if record_id == next_id {
+                if index_item.records_offset < 8 {
+                    return Err(ErrorKind::InvalidPhoneDatabase);
+                }
+                let record = self.parse_to_record(&data, index_item.records_offset as usize)?;
+                parsed.push([
+                    record.province,
+                    record.city,
+                    record.zip_code,
+                    record.area_code,
+                ]);
+            }
+            index.push((index_item.phone_no_prefix, record_id, index_item.card_type));
+        }
+        for fields in &parsed {
+            let mut ids = [0usize; 4];
+            for (id, field) in ids.iter_mut().zip(fields) {
+                *id = match string_ids.get(field) {
+                    Some(&id) => id,
+                    None => {
+                        string_ids.insert(field.clone(), strings.len());
+                        strings.push(field);
+                        strings.len() - 1
+                    }
+                };
+            }
+            records.push(ids);
+        }
+
+        let mut out = Vec::new();
+        out.extend_from_slice(&COMPACT_MAGIC);
+        out.push(COMPACT_FORMAT_VERSION);
+        let mut version = [b' '; 4];
+        for (dst, src) in version.iter_mut().zip(data.version.bytes()) {
+            *dst = src;
+        }
+        out.extend_from_slice(&version);
+
+        write_varint(&mut out, strings.len() as u64);
+        for s in &strings {
+            write_varint(&mut out, s.len() as u64);
+            out.extend_from_slice(s.as_bytes());
+        }
+        write_varint(&mut out, records.len() as u64);
+        for ids in &records {
+            for &id in ids {
+                write_varint(&mut out, id as u64);
+            }
+        }
+        write_varint(&mut out, index.len() as u64);
+        let mut previous = 0i64;
+        for (prefix, record_id, card_type) in index {
+            let delta = prefix as i64 - previous;
+            previous = prefix as i64;
+            write_varint(&mut out, zigzag(delta));
+            write_varint(&mut out, record_id as u64);
+            out.push(card_type);
+        }
+
+        w.write_all(&out)?;
+        Ok(())
+    }
+
+    /// 加载 [`PhoneData::write_compact`] 写出的紧凑格式数据库
+    ///
+    /// 加载后的实例与原格式加载的实例行为一致，记录字段使用默认分隔符 `|` 重新拼接。
+    pub fn from_compact<R: Read>(mut reader: R) -> Fallible<PhoneData> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let mut input = Decoder { bytes: &bytes };
+
+        if input.take(4)? != COMPACT_MAGIC {
+            return Err(ErrorKind::InvalidPhoneDatabase);
+        }
+        if input.take(1)?[0] != COMPACT_FORMAT_VERSION {
+            return Err(ErrorKind::InvalidPhoneDatabase);
+        }
+        let version = input.take(4)?.to_vec();
+
+        let string_count = input.len_prefix()?;
+        // 计数来自文件内容，预分配时以剩余字节数为上限，避免损坏的文件导致巨额分配
+        let mut strings = Vec::with_capacity(string_count.min(input.bytes.len()));
+        for _ in 0..string_count {
+            let len = input.len_prefix()?;
+            let s = std::str::from_utf8(input.take(len)?)
+                .map_err(|_| ErrorKind::InvalidPhoneDatabase)?;
+            strings.push(s);
+        }
+
+        // 重建原格式的记录区，记录编号 -> 绝对偏移
+        let record_count = input.len_prefix()?;
+        let mut records = Vec::new();
+        let mut offsets = Vec::with_capacity(record_count.min(input.bytes.len()));
+        for _ in 0..record_count {
+            offsets.push((records.len() + 8) as i32);
+            for field in 0..4 {
+                let id = input.len_prefix()?;
+                let s = strings.get(id).ok_or(ErrorKind::InvalidPhoneDatabase)?;
+                if field > 0 {
+                    let mut buf = [0u8; 4];
+                    records.extend_from_slice(
+                        DEFAULT_RECORD_DELIMITER.encode_utf8(&mut buf).as_bytes(),
+                    );
+                }
+                records.extend_from_slice(s.as_bytes());
+            }
+            records.push(0);
+        }
+
+        let index_count = input.len_prefix()?;
+        let mut out =
+            Vec::with_capacity(8 + records.len() + index_count.min(input.bytes.len()) * 9);
+        out.extend_from_slice(&version);
+        out.extend_from_slice(&((records.len() + 8) as i32).to_le_bytes());
+        out.extend_from_slice(&records);
+        let mut prefix = 0i64;
+        for _ in 0..index_count {
+            prefix += unzigzag(input.varint()?);
+            let prefix = i32::try_from(prefix).map_err(|_| ErrorKind::InvalidPhoneDatabase)?;
+            let record_id = input.len_prefix()?;
+            let offset = *offsets
+                .get(record_id)
+                .ok_or(ErrorKind::InvalidPhoneDatabase)?;
+            let card_type = input.take(1)?[0];
+            out.extend_from_slice(&prefix.to_le_bytes());
+            out.extend_from_slice(&offset.to_le_bytes());
+            out.push(card_type);
+        }
+        if !input.bytes.is_empty() {
+            return Err(ErrorKind::InvalidPhoneDatabase);
+        }
+
+        Self::from_bytes(&out)
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+/// 紧凑格式的顺序读取器，任何越界读取都视为文件损坏
+struct Decoder<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, len: usize) -> Fallible<&'a [u8]> {
+        if self.bytes.len() < len {
+            return Err(ErrorKind::InvalidPhoneDatabase);
+        }
+        let (head, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(head)
+    }
+
+    fn varint(&mut self) -> Fallible<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(ErrorKind::InvalidPhoneDatabase)
+    }
+
+    /// 读取长度、数量或编号
+    fn len_prefix(&mut self) -> Fallible<usize> {
+        usize::try_from(self.varint()?).map_err(|_| ErrorKind::InvalidPhoneDatabase)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::create_mock_phone_data_with;
+
+    #[test]
+    fn test_compact_round_trip() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380014, "北京|北京|100000|010", 2),
+            (1380755, "广东|深圳|518000|0755", 3),
+            (1380756, "广东|广州|510000|020", 7),
+        ]);
+
+        let mut compact = Vec::new();
+        phone_data.write_compact(&mut compact).unwrap();
+        assert_eq!(&compact[..4], b"PLRC");
+        assert_eq!(compact[4], COMPACT_FORMAT_VERSION);
+
+        let loaded = PhoneData::from_compact(&compact[..]).unwrap();
+        assert_eq!(loaded.version(), phone_data.version());
+        assert_eq!(loaded.index_count(), phone_data.index_count());
+        for no in ["13800138000", "13800148000", "13807550000", "13807560000"] {
+            assert_eq!(loaded.find(no).unwrap(), phone_data.find(no).unwrap());
+        }
+        assert!(matches!(
+            loaded.find("13900000000"),
+            Err(ErrorKind::NotFound)
+        ));
+    }
+
+    #[test]
+    fn test_compact_bundled_database() {
+        let phone_data = PhoneData::new().unwrap();
+        let mut compact = Vec::new();
+        phone_data.write_compact(&mut compact).unwrap();
+        let raw_len = std::fs::metadata("phone.dat").unwrap().len() as usize;
+        assert!(compact.len() < raw_len / 2);
+
+        let loaded = PhoneData::from_compact(&compact[..]).unwrap();
+        assert_eq!(loaded.index_count(), phone_data.index_count());
+        for no in ["13800138000", "18000000000", "19200000000", "13005400000"] {
+            assert_eq!(loaded.find(no).unwrap(), phone_data.find(no).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_compact_rejects_corrupt_input() {
+        let phone_data = create_mock_phone_data_with(&[(1380013, "北京|北京|100000|010", 1)]);
+        let mut compact = Vec::new();
+        phone_data.write_compact(&mut compact).unwrap();
+
+        assert!(PhoneData::from_compact(&compact[..compact.len() - 1]).is_err());
+        let mut wrong_magic = compact.clone();
+        wrong_magic[0] = b'X';
+        assert!(PhoneData::from_compact(&wrong_magic[..]).is_err());
+        assert!(PhoneData::from_compact(&b"PLRC"[..]).is_err());
+    }
+}
@@ -1,6 +1,17 @@
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::net::{SocketAddr, ToSocketAddrs};
 use std::path::Path;
 
+/// 敏感字段被隐藏后的占位值
+pub const REDACTED: &str = "***";
+
+/// [`Config::load`] 使用的配置文件路径
+pub const DEFAULT_CONFIG_PATH: &str = "config.toml";
+
+/// [`Config::load`] 使用的环境变量前缀
+pub const DEFAULT_ENV_PREFIX: &str = "PHONE_DATA";
+
 #[derive(Debug, Deserialize, Serialize, Clone, Default)]
 pub struct Config {
     #[serde(default)]
@@ -12,6 +23,8 @@ pub struct Config {
     #[serde(default)]
     pub cache: CacheConfig,
     #[serde(default)]
+    pub batch: BatchConfig,
+    #[serde(default)]
     pub logging: LoggingConfig,
 }
 
@@ -35,6 +48,26 @@ pub struct ServerConfig {
     pub host: String,
     pub port: u16,
     pub workers: usize,
+    /// 监听地址列表（如 `["0.0.0.0:8080", "[::]:8080"]`），为空时使用 `host`/`port`
+    #[serde(default)]
+    pub binds: Vec<String>,
+    /// 管理接口（如 `/config`）的访问令牌，未设置时管理接口不可用
+    #[serde(default)]
+    pub admin_token: Option<String>,
+    /// 批量查询全部失败时返回的 HTTP 状态码，默认 200（与部分成功相同），可设为 422 等
+    #[serde(default = "default_batch_fail_status")]
+    pub batch_fail_status: u16,
+    /// 单号查询成功时 `Cache-Control: max-age` 的秒数，0 表示不发送该响应头
+    #[serde(default = "default_result_cache_seconds")]
+    pub result_cache_seconds: u64,
+}
+
+fn default_batch_fail_status() -> u16 {
+    200
+}
+
+fn default_result_cache_seconds() -> u64 {
+    300
 }
 
 impl Default for ServerConfig {
@@ -43,10 +76,43 @@ impl Default for ServerConfig {
             host: "0.0.0.0".to_string(),
             port: 8080,
             workers: 0, // 0 = auto detect
+            binds: Vec::new(),
+            admin_token: None,
+            batch_fail_status: default_batch_fail_status(),
+            result_cache_seconds: default_result_cache_seconds(),
         }
     }
 }
 
+impl ServerConfig {
+    /// 解析服务器需要监听的全部地址
+    ///
+    /// 配置了 `binds` 时逐个解析其中的地址，否则回退到 `host:port`。
+    ///
+    /// # 错误
+    ///
+    /// 任一地址无法解析时返回包含该地址的错误信息
+    pub fn bind_addresses(&self) -> Result<Vec<SocketAddr>, Box<dyn std::error::Error>> {
+        if self.binds.is_empty() {
+            let addrs = (self.host.as_str(), self.port)
+                .to_socket_addrs()
+                .map_err(|e| format!("无效的监听地址: {}:{} ({})", self.host, self.port, e))?;
+            return Ok(addrs.take(1).collect());
+        }
+
+        let mut result = Vec::with_capacity(self.binds.len());
+        for bind in &self.binds {
+            let addr = bind
+                .to_socket_addrs()
+                .map_err(|e| format!("无效的监听地址: {} ({})", bind, e))?
+                .next()
+                .ok_or_else(|| format!("无效的监听地址: {}", bind))?;
+            result.push(addr);
+        }
+        Ok(result)
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct DatabaseConfig {
     pub path: String,
@@ -64,6 +130,19 @@ impl Default for DatabaseConfig {
 pub struct CacheConfig {
     pub enabled: bool,
     pub max_size: usize,
+    /// 淘汰策略：`lru`、`lfu` 或 `fifo`
+    #[serde(default = "default_eviction")]
+    pub eviction: String,
+    /// 启动时为每个号段的代表号码预热结果缓存（受 `max_size` 限制）
+    #[serde(default)]
+    pub warm_up: bool,
+    /// 缓存条目的存活时间（秒），未设置时永不过期
+    #[serde(default)]
+    pub ttl_seconds: Option<u64>,
+}
+
+fn default_eviction() -> String {
+    "lru".to_string()
 }
 
 impl Default for CacheConfig {
@@ -71,6 +150,34 @@ impl Default for CacheConfig {
         Self {
             enabled: true,
             max_size: 1000,
+            eviction: default_eviction(),
+            warm_up: false,
+            ttl_seconds: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct BatchConfig {
+    /// 单次批量查询允许的最大号码数
+    pub max_size: usize,
+    /// 同时执行查询的任务数
+    pub concurrency: usize,
+    /// 整个批量操作的超时（毫秒），超时后未完成的号码标记为查询超时，0 表示不限制
+    #[serde(default = "default_batch_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_batch_timeout_ms() -> u64 {
+    5000
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            max_size: 100,
+            concurrency: 4,
+            timeout_ms: default_batch_timeout_ms(),
         }
     }
 }
@@ -79,6 +186,27 @@ impl Default for CacheConfig {
 pub struct LoggingConfig {
     pub level: String,
     pub format: String,
+    /// 慢查询告警阈值（毫秒），0 表示关闭
+    #[serde(default)]
+    pub slow_query_ms: u64,
+    /// 日志中的手机号是否脱敏（第4-7位替换为星号）
+    #[serde(default = "default_mask_phone")]
+    pub mask_phone: bool,
+}
+
+fn default_mask_phone() -> bool {
+    true
+}
+
+impl LoggingConfig {
+    /// 按 `mask_phone` 设置返回写入日志的号码
+    pub fn phone_for_log<'a>(&self, no: &'a str) -> Cow<'a, str> {
+        if self.mask_phone {
+            Cow::Owned(crate::mask_number(no))
+        } else {
+            Cow::Borrowed(no)
+        }
+    }
 }
 
 impl Default for LoggingConfig {
@@ -86,6 +214,8 @@ impl Default for LoggingConfig {
         Self {
             level: "info".to_string(),
             format: "pretty".to_string(),
+            slow_query_ms: 0,
+            mask_phone: default_mask_phone(),
         }
     }
 }
@@ -106,22 +236,42 @@ impl Config {
     /// 
     /// 当配置文件格式错误或配置验证失败时返回错误
     pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
+        Self::load_from(Path::new(DEFAULT_CONFIG_PATH))
+    }
+
+    /// 从指定路径的配置文件加载，环境变量前缀为 `PHONE_DATA`
+    ///
+    /// 优先级与 [`Config::load`] 相同，文件不存在时使用默认配置。
+    pub fn load_from(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::load_from_with_env_prefix(path, DEFAULT_ENV_PREFIX)
+    }
+
+    /// 从指定路径的配置文件加载，并使用指定前缀的环境变量覆盖
+    ///
+    /// 环境变量形如 `{prefix}_CACHE__MAX_SIZE`，多实例部署时可为每个实例使用不同前缀。
+    pub fn load_from_with_env_prefix(
+        path: &Path,
+        env_prefix: &str,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
         let mut settings =
             config::Config::builder().add_source(config::Config::try_from(&Config::default())?);
 
         // 尝试加载配置文件
-        if Path::new("config.toml").exists() {
-            settings = settings.add_source(config::File::with_name("config"));
-            tracing::info!("已加载配置文件: config.toml");
+        if path.exists() {
+            settings = settings.add_source(config::File::from(path));
+            tracing::info!("已加载配置文件: {}", path.display());
         } else {
-            tracing::info!("未找到配置文件，使用默认配置");
+            tracing::info!("未找到配置文件 {}，使用默认配置", path.display());
         }
 
         // 环境变量覆盖
         settings = settings.add_source(
-            config::Environment::with_prefix("PHONE_DATA")
+            config::Environment::with_prefix(env_prefix)
                 .prefix_separator("_")
-                .separator("__"),
+                .separator("__")
+                .try_parsing(true)
+                .list_separator(",")
+                .with_list_parse_key("server.binds"),
         );
 
         let config: Config = settings.build()?.try_deserialize()?;
@@ -133,6 +283,15 @@ impl Config {
         Ok(config)
     }
 
+    /// 返回隐藏了敏感字段（管理令牌）的配置副本，用于对外展示
+    pub fn redacted(&self) -> Config {
+        let mut config = self.clone();
+        if config.server.admin_token.is_some() {
+            config.server.admin_token = Some(REDACTED.to_string());
+        }
+        config
+    }
+
     /// 验证配置参数的有效性
     /// 
     /// # 错误
@@ -143,6 +302,14 @@ impl Config {
         if self.server.port == 0 {
             return Err("服务器端口不能为0".into());
         }
+        self.server.bind_addresses()?;
+        if !(200..=599).contains(&self.server.batch_fail_status) {
+            return Err(format!(
+                "无效的批量查询失败状态码: {}",
+                self.server.batch_fail_status
+            )
+            .into());
+        }
 
         // 验证数据库配置
         if self.database.path.is_empty() {
@@ -156,10 +323,22 @@ impl Config {
         if self.cache.max_size == 0 && self.cache.enabled {
             return Err("启用缓存时，缓存大小不能为0".into());
         }
+        self.cache.eviction.parse::<crate::EvictionPolicy>()?;
+        if self.cache.ttl_seconds == Some(0) {
+            return Err("缓存 TTL 不能为0".into());
+        }
         if self.cache.max_size > 1_000_000 {
             tracing::warn!("缓存大小过大({}),可能影响内存使用", self.cache.max_size);
         }
 
+        // 验证批量查询配置
+        if self.batch.max_size == 0 {
+            return Err("批量查询上限不能为0".into());
+        }
+        if self.batch.concurrency == 0 {
+            return Err("批量查询并发数不能为0".into());
+        }
+
         // 验证日志配置
         let valid_levels = ["error", "warn", "info", "debug", "trace"];
         if !valid_levels.contains(&self.logging.level.as_str()) {
@@ -176,3 +355,127 @@ impl Config {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_multiple_binds() {
+        let config: Config = toml::from_str(
+            r#"
+            [server]
+            host = "0.0.0.0"
+            port = 8088
+            workers = 0
+            binds = ["127.0.0.1:8088", "[::1]:8089"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.server.binds.len(), 2);
+        let addrs = config.server.bind_addresses().unwrap();
+        assert_eq!(
+            addrs,
+            vec![
+                "127.0.0.1:8088".parse::<SocketAddr>().unwrap(),
+                "[::1]:8089".parse::<SocketAddr>().unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bind_addresses_fallback_to_host_port() {
+        let server = ServerConfig {
+            host: "127.0.0.1".to_string(),
+            port: 9000,
+            ..ServerConfig::default()
+        };
+        let addrs = server.bind_addresses().unwrap();
+        assert_eq!(addrs, vec!["127.0.0.1:9000".parse::<SocketAddr>().unwrap()]);
+    }
+
+    #[test]
+    fn test_bind_addresses_invalid() {
+        let server = ServerConfig {
+            binds: vec!["not an address".to_string()],
+            ..ServerConfig::default()
+        };
+        assert!(server.bind_addresses().is_err());
+    }
+
+    #[test]
+    fn test_validate_eviction_policy() {
+        let mut config = Config::default();
+        assert!(config.validate().is_ok());
+        config.cache.eviction = "FIFO".to_string();
+        assert!(config.validate().is_ok());
+        config.cache.eviction = "random".to_string();
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("random"));
+    }
+
+    #[test]
+    fn test_load_from_custom_path() {
+        let dir = std::env::temp_dir().join(format!("phone_lookup_config_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("instance-a.toml");
+        std::fs::write(
+            &path,
+            r#"
+            [server]
+            host = "127.0.0.1"
+            port = 9123
+            workers = 2
+
+            [cache]
+            enabled = true
+            max_size = 42
+            "#,
+        )
+        .unwrap();
+        std::env::set_var("PHONE_LOOKUP_TEST_LOAD_FROM_BATCH__MAX_SIZE", "7");
+
+        let config =
+            Config::load_from_with_env_prefix(&path, "PHONE_LOOKUP_TEST_LOAD_FROM").unwrap();
+        std::env::remove_var("PHONE_LOOKUP_TEST_LOAD_FROM_BATCH__MAX_SIZE");
+        let missing = Config::load_from(&dir.join("missing.toml")).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(config.server.port, 9123);
+        assert_eq!(config.server.workers, 2);
+        assert_eq!(config.cache.max_size, 42);
+        assert_eq!(config.batch.max_size, 7);
+        // 文件中未出现的字段保持默认值
+        assert_eq!(config.cache.eviction, "lru");
+        assert_eq!(config.database.path, "phone.dat");
+        assert_eq!(missing.server.port, ServerConfig::default().port);
+    }
+
+    #[test]
+    fn test_phone_for_log() {
+        let mut logging = LoggingConfig::default();
+        assert_eq!(logging.phone_for_log("13800138000"), "138****8000");
+        logging.mask_phone = false;
+        assert_eq!(logging.phone_for_log("13800138000"), "13800138000");
+    }
+
+    #[test]
+    fn test_validate_batch_config() {
+        let config: Config = toml::from_str(
+            r#"
+            [batch]
+            max_size = 500
+            concurrency = 8
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.batch.max_size, 500);
+        assert_eq!(config.batch.timeout_ms, 5000);
+        assert!(config.validate().is_ok());
+
+        let mut config = Config::default();
+        config.batch.concurrency = 0;
+        assert!(config.validate().unwrap_err().to_string().contains("并发"));
+    }
+}
@@ -0,0 +1,302 @@
+//! 多数据库组合查询
+//!
+//! 把一个基础库和若干覆盖库（补丁）组合在一起查询，靠后的成员优先级更高；
+//! 也提供不同数据源之间的运营商变化和全量差异对比。
+
+use std::sync::Arc;
+
+use crate::{CardType, ErrorKind, Fallible, PhoneData, PhoneNoInfo};
+
+/// 按优先级组合的多个数据库
+///
+/// 第一个成员为基础库，之后的成员依次覆盖前面的成员：查询时从最后一个成员开始，
+/// 第一个命中的成员给出结果。某个成员未找到时继续查询前一个成员，
+/// 其他错误（如号码格式错误）直接返回。
+#[derive(Debug, Clone, Default)]
+pub struct PhoneDataSet {
+    members: Vec<Arc<PhoneData>>,
+}
+
+impl PhoneDataSet {
+    /// 用基础库创建组合
+    pub fn new(base: Arc<PhoneData>) -> Self {
+        PhoneDataSet {
+            members: vec![base],
+        }
+    }
+
+    /// 追加一个优先级最高的覆盖库
+    pub fn push_overlay(&mut self, overlay: Arc<PhoneData>) {
+        self.members.push(overlay);
+    }
+
+    /// 成员数量
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    /// 是否没有任何成员
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// 按索引获取成员，0 为基础库
+    pub fn member(&self, index: usize) -> Option<&Arc<PhoneData>> {
+        self.members.get(index)
+    }
+
+    /// 查询号码归属地
+    pub fn find(&self, no: &str) -> Fallible<PhoneNoInfo> {
+        self.find_with_source(no).map(|(info, _)| info)
+    }
+
+    /// 查询号码归属地，同时返回给出结果的成员索引（0 为基础库）
+    ///
+    /// 用于确认覆盖库是否按预期生效。
+    pub fn find_with_source(&self, no: &str) -> Fallible<(PhoneNoInfo, usize)> {
+        for (source, member) in self.members.iter().enumerate().rev() {
+            match member.find(no) {
+                Ok(info) => return Ok((info, source)),
+                Err(ErrorKind::NotFound) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Err(ErrorKind::NotFound)
+    }
+}
+
+/// 同一号码在新旧两个数据库中的运营商
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CarrierChange {
+    /// 旧库中的运营商
+    pub old: CardType,
+    /// 新库中的运营商
+    pub new: CardType,
+    /// 运营商是否发生变化
+    pub changed: bool,
+}
+
+/// 比较号码在新旧两个数据库中的运营商
+///
+/// 只查询单个号码，比全量对比两个数据库轻量得多。号码在任一数据库中查询失败时返回对应错误。
+pub fn carrier_change(old: &PhoneData, new: &PhoneData, no: &str) -> Fallible<CarrierChange> {
+    let old = old.find_card_type(no)?;
+    let new = new.find_card_type(no)?;
+    Ok(CarrierChange {
+        old,
+        new,
+        changed: old != new,
+    })
+}
+
+/// 号段差异的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrefixDiffKind {
+    /// 只有第一个数据库包含该号段
+    OnlyInA,
+    /// 只有第二个数据库包含该号段
+    OnlyInB,
+    /// 两个数据库都包含该号段，但省份、城市或运营商不同
+    Mismatch,
+}
+
+/// 两个数据库对同一号段的判断差异，见 [`diff_databases`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefixDiff {
+    /// 7位号段前缀
+    pub prefix: u32,
+    /// 差异类型
+    pub kind: PrefixDiffKind,
+    /// 号段在第一个数据库中的归属地，不包含该号段时为 `None`
+    pub a: Option<PhoneNoInfo>,
+    /// 号段在第二个数据库中的归属地，不包含该号段时为 `None`
+    pub b: Option<PhoneNoInfo>,
+}
+
+/// 全量对比两个数据库，返回按号段升序排列的差异列表
+///
+/// 对两库的号段取并集：同时存在的号段比较省份、城市和运营商（按 [`CardType::icon_key`]
+/// 比较，不受自定义运营商名称影响），仅存在于一方的号段也会列出。
+/// 记录无法解析或运营商无法识别的号段视为不存在；同一号段重复出现时只取第一项。
+/// 不经过结果缓存，也不计入查询统计。
+pub fn diff_databases(a: &PhoneData, b: &PhoneData) -> Vec<PrefixDiff> {
+    let data_a = a.snapshot();
+    let data_b = b.snapshot();
+    let (mut i, mut j) = (0, 0);
+    let mut diffs = Vec::new();
+    while i < data_a.index.len() || j < data_b.index.len() {
+        let prefix_a = data_a.index.get(i).map(|idx| idx.phone_no_prefix);
+        let prefix_b = data_b.index.get(j).map(|idx| idx.phone_no_prefix);
+        let prefix = match (prefix_a, prefix_b) {
+            (Some(x), Some(y)) => x.min(y),
+            (Some(x), None) => x,
+            (None, Some(y)) => y,
+            (None, None) => break,
+        };
+        let info_a = (prefix_a == Some(prefix))
+            .then(|| a.info_at(&data_a, i).ok())
+            .flatten();
+        let info_b = (prefix_b == Some(prefix))
+            .then(|| b.info_at(&data_b, j).ok())
+            .flatten();
+        // 跳过当前号段的所有重复项
+        while data_a
+            .index
+            .get(i)
+            .is_some_and(|idx| idx.phone_no_prefix == prefix)
+        {
+            i += 1;
+        }
+        while data_b
+            .index
+            .get(j)
+            .is_some_and(|idx| idx.phone_no_prefix == prefix)
+        {
+            j += 1;
+        }
+
+        let kind = match (&info_a, &info_b) {
+            (Some(x), Some(y))
+                if x.province == y.province && x.city == y.city && x.icon_key == y.icon_key =>
+            {
+                continue
+            }
+            (Some(_), Some(_)) => PrefixDiffKind::Mismatch,
+            (Some(_), None) => PrefixDiffKind::OnlyInA,
+            (None, Some(_)) => PrefixDiffKind::OnlyInB,
+            (None, None) => continue,
+        };
+        diffs.push(PrefixDiff {
+            prefix: prefix as u32,
+            kind,
+            a: info_a,
+            b: info_b,
+        });
+    }
+    diffs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::create_mock_phone_data_with;
+
+    fn create_base_and_overlay() -> PhoneDataSet {
+        let base = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380014, "北京|北京|100000|010", 1),
+        ]);
+        let overlay = create_mock_phone_data_with(&[
+            (1380014, "河北|石家庄|050000|0311", 1),
+            (1380015, "河北|保定|071000|0312", 1),
+        ]);
+
+        let mut set = PhoneDataSet::new(Arc::new(base));
+        set.push_overlay(Arc::new(overlay));
+        set
+    }
+
+    #[test]
+    fn test_find_with_source() {
+        let set = create_base_and_overlay();
+        assert_eq!(set.len(), 2);
+
+        let (info, source) = set.find_with_source("13800138000").unwrap();
+        assert_eq!(info.city, "北京");
+        assert_eq!(source, 0);
+
+        let (info, source) = set.find_with_source("13800148000").unwrap();
+        assert_eq!(info.city, "石家庄");
+        assert_eq!(source, 1);
+
+        let (info, source) = set.find_with_source("13800158000").unwrap();
+        assert_eq!(info.city, "保定");
+        assert_eq!(source, 1);
+    }
+
+    #[test]
+    fn test_find_with_source_errors() {
+        let set = create_base_and_overlay();
+        assert!(matches!(
+            set.find_with_source("13900000000"),
+            Err(ErrorKind::NotFound)
+        ));
+        assert!(matches!(
+            set.find_with_source("123"),
+            Err(ErrorKind::InvalidLength)
+        ));
+    }
+
+    #[test]
+    fn test_carrier_change_between_databases() {
+        let old = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380014, "北京|北京|100000|010", 1),
+        ]);
+        let new = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 2),
+            (1380014, "北京|北京|100000|010", 1),
+        ]);
+
+        let change = carrier_change(&old, &new, "13800138000").unwrap();
+        assert_eq!(change.old, CardType::Cmcc);
+        assert_eq!(change.new, CardType::Cucc);
+        assert!(change.changed);
+
+        let unchanged = carrier_change(&old, &new, "13800148000").unwrap();
+        assert!(!unchanged.changed);
+
+        assert!(matches!(
+            carrier_change(&old, &new, "13800158000"),
+            Err(ErrorKind::NotFound)
+        ));
+    }
+
+    #[test]
+    fn test_diff_databases() {
+        let a = create_mock_phone_data_with(&[
+            (1380010, "北京|北京|100000|010", 1),
+            (1380011, "北京|北京|100000|010", 1),
+            (1380012, "广东|深圳|518000|0755", 1),
+            (1380013, "广东|深圳|518000|0755", 1),
+            (1380015, "广东|广州|510000|020", 3),
+        ]);
+        let b = create_mock_phone_data_with(&[
+            (1380009, "上海|上海|200000|021", 2),
+            (1380010, "北京|北京|100000|010", 1),
+            (1380011, "北京|北京|100000|010", 2),
+            (1380012, "广东|东莞|523000|0769", 1),
+            (1380015, "广东|广州|510000|020", 3),
+            (1380016, "广东|广州|510000|020", 3),
+        ]);
+
+        let diffs = diff_databases(&a, &b);
+        let summary: Vec<(u32, PrefixDiffKind)> =
+            diffs.iter().map(|diff| (diff.prefix, diff.kind)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1380009, PrefixDiffKind::OnlyInB),
+                (1380011, PrefixDiffKind::Mismatch),
+                (1380012, PrefixDiffKind::Mismatch),
+                (1380013, PrefixDiffKind::OnlyInA),
+                (1380016, PrefixDiffKind::OnlyInB),
+            ]
+        );
+
+        let carrier = &diffs[1];
+        assert_eq!(carrier.a.as_ref().unwrap().card_type, "中国移动");
+        assert_eq!(carrier.b.as_ref().unwrap().card_type, "中国联通");
+        let city = &diffs[2];
+        assert_eq!(city.a.as_ref().unwrap().city, "深圳");
+        assert_eq!(city.b.as_ref().unwrap().city, "东莞");
+        assert!(diffs[0].a.is_none());
+        assert!(diffs[3].b.is_none());
+
+        assert!(diff_databases(&a, &a).is_empty());
+        let reversed = diff_databases(&b, &a);
+        assert_eq!(reversed.len(), diffs.len());
+        assert_eq!(reversed[0].kind, PrefixDiffKind::OnlyInA);
+        assert_eq!(a.query_count(), 0);
+    }
+}
@@ -0,0 +1,249 @@
+//! 批量数据处理
+//!
+//! 逐行读取号码，并行查询后写出 CSV，便于在 ETL 任务中直接使用；
+//! 也可以把整个数据库按号段导出为 CSV 或 JSON。
+
+use std::io::{BufRead, Write};
+
+use serde::Serialize;
+
+use crate::{Fallible, PhoneData, PhoneNoInfo};
+
+/// 每个工作线程每批处理的行数
+const LINES_PER_WORKER: usize = 256;
+
+/// CSV 表头
+const CSV_HEADER: &str = "phone,province,city,zip_code,area_code,card_type";
+
+/// 导出数据库时的 CSV 表头
+const EXPORT_CSV_HEADER: &str = "prefix,province,city,zip_code,area_code,card_type";
+
+/// 导出数据库时每个号段对应的 JSON 对象
+#[derive(Serialize)]
+struct ExportRow<'a> {
+    prefix: u32,
+    province: &'a str,
+    city: &'a str,
+    zip_code: &'a str,
+    area_code: &'a str,
+    card_type: &'a str,
+}
+
+/// [`PhoneData::enrich_stream`] 的处理统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EnrichStats {
+    /// 查询成功的行数
+    pub succeeded: u64,
+    /// 查询失败的行数
+    pub failed: u64,
+}
+
+impl PhoneData {
+    /// 逐行读取号码并写出带归属地列的 CSV
+    ///
+    /// 输出首行为表头，之后每个非空输入行对应一行输出，顺序与输入一致；
+    /// 查询失败的号码保留原始输入，归属地列留空。输入按批读取，
+    /// 每批最多由 `workers` 个线程并行查询，内存占用与输入总量无关。
+    pub fn enrich_stream<R: BufRead, W: Write>(
+        &self,
+        input: R,
+        mut output: W,
+        workers: usize,
+    ) -> Fallible<EnrichStats> {
+        let workers = workers.max(1);
+        let batch_size = workers * LINES_PER_WORKER;
+        let mut stats = EnrichStats::default();
+        writeln!(output, "{}", CSV_HEADER)?;
+
+        let mut lines = input.lines();
+        loop {
+            let mut batch = Vec::with_capacity(batch_size);
+            for line in lines.by_ref() {
+                let line = line?;
+                let line = line.trim();
+                if !line.is_empty() {
+                    batch.push(line.to_string());
+                }
+                if batch.len() == batch_size {
+                    break;
+                }
+            }
+            if batch.is_empty() {
+                break;
+            }
+
+            let chunk_size = batch.len().div_ceil(workers);
+            let results: Vec<Vec<Fallible<PhoneNoInfo>>> = std::thread::scope(|scope| {
+                let handles: Vec<_> = batch
+                    .chunks(chunk_size)
+                    .map(|chunk| {
+                        scope.spawn(move || chunk.iter().map(|no| self.find(no)).collect())
+                    })
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|handle| handle.join().expect("enrich worker panicked"))
+                    .collect()
+            });
+
+            for (no, result) in batch.iter().zip(results.into_iter().flatten()) {
+                match result {
+                    Ok(info) => {
+                        stats.succeeded += 1;
+                        writeln!(
+                            output,
+                            "{},{},{},{},{},{}",
+                            csv_field(no),
+                            csv_field(&info.province),
+                            csv_field(&info.city),
+                            csv_field(&info.zip_code),
+                            csv_field(&info.area_code),
+                            csv_field(&info.card_type)
+                        )?;
+                    }
+                    Err(_) => {
+                        stats.failed += 1;
+                        writeln!(output, "{},,,,,", csv_field(no))?;
+                    }
+                }
+            }
+        }
+
+        output.flush()?;
+        Ok(stats)
+    }
+
+    /// 按号段前缀顺序把整个数据库导出为 CSV
+    ///
+    /// 首行为表头，之后每个号段一行，运营商列为中文描述；跳过的号段与 [`PhoneData::iter`] 相同。
+    /// 字段包含逗号、引号或换行时按 CSV 规则加引号。
+    pub fn export_csv<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writeln!(writer, "{}", EXPORT_CSV_HEADER)?;
+        for (prefix, info) in self.iter() {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{}",
+                prefix,
+                csv_field(&info.province),
+                csv_field(&info.city),
+                csv_field(&info.zip_code),
+                csv_field(&info.area_code),
+                csv_field(&info.card_type)
+            )?;
+        }
+        writer.flush()
+    }
+
+    /// 按号段前缀顺序把整个数据库导出为 JSON 数组，字段与 [`PhoneData::export_csv`] 的列相同
+    ///
+    /// 逐个号段写出，不会在内存中构建整个数组。
+    pub fn export_json<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writer.write_all(b"[")?;
+        for (i, (prefix, info)) in self.iter().enumerate() {
+            if i > 0 {
+                writer.write_all(b",")?;
+            }
+            let row = ExportRow {
+                prefix,
+                province: &info.province,
+                city: &info.city,
+                zip_code: &info.zip_code,
+                area_code: &info.area_code,
+                card_type: &info.card_type,
+            };
+            serde_json::to_writer(&mut writer, &row)?;
+        }
+        writer.write_all(b"]")?;
+        writer.flush()
+    }
+}
+
+/// 按 CSV 规则转义字段：包含逗号、引号或换行时加引号
+fn csv_field(value: &str) -> std::borrow::Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\"")).into()
+    } else {
+        value.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::create_mock_phone_data_with;
+
+    #[test]
+    fn test_enrich_stream() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380755, "广东|深圳|518000|0755", 2),
+        ]);
+        let input = "13800138000\n\n13807550000\n1390000,x\n13800130001\n";
+
+        let mut output = Vec::new();
+        let stats = phone_data
+            .enrich_stream(input.as_bytes(), &mut output, 2)
+            .unwrap();
+
+        assert_eq!(
+            stats,
+            EnrichStats {
+                succeeded: 3,
+                failed: 1
+            }
+        );
+        let output = String::from_utf8(output).unwrap();
+        let rows: Vec<&str> = output.lines().collect();
+        assert_eq!(
+            rows,
+            vec![
+                CSV_HEADER,
+                "13800138000,北京,北京,100000,010,中国移动",
+                "13807550000,广东,深圳,518000,0755,中国联通",
+                "\"1390000,x\",,,,,",
+                "13800130001,北京,北京,100000,010,中国移动",
+            ]
+        );
+    }
+
+    fn export_fixture() -> PhoneData {
+        create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380014, "北京|北京|100000|010", 2),
+            (1380755, "广东|深圳,南山|518000|0755", 3),
+        ])
+    }
+
+    #[test]
+    fn test_export_csv() {
+        let phone_data = export_fixture();
+        let mut output = Vec::new();
+        phone_data.export_csv(&mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let rows: Vec<&str> = output.lines().collect();
+        assert_eq!(rows.len(), phone_data.index_count() + 1);
+        assert_eq!(rows[0], EXPORT_CSV_HEADER);
+        assert_eq!(rows[1], "1380013,北京,北京,100000,010,中国移动");
+        assert_eq!(rows[2], "1380014,北京,北京,100000,010,中国联通");
+        assert_eq!(rows[3], "1380755,广东,\"深圳,南山\",518000,0755,中国电信");
+    }
+
+    #[test]
+    fn test_export_json() {
+        let phone_data = export_fixture();
+        let mut output = Vec::new();
+        phone_data.export_json(&mut output).unwrap();
+
+        let rows: Vec<serde_json::Value> = serde_json::from_slice(&output).unwrap();
+        assert_eq!(rows.len(), phone_data.index_count());
+        assert_eq!(rows[0]["prefix"], 1380013);
+        assert_eq!(rows[1]["card_type"], "中国联通");
+        assert_eq!(rows[2]["city"], "深圳,南山");
+
+        let empty = create_mock_phone_data_with(&[]);
+        let mut output = Vec::new();
+        empty.export_json(&mut output).unwrap();
+        assert_eq!(output, b"[]");
+    }
+}
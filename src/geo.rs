@@ -0,0 +1,141 @@
+//! 归属地经纬度扩展数据
+//!
+//! 数据库本身不含坐标。扩展数据为 CSV 文件，每行 `province,city,lat,lon`，可带同名表头，
+//! 空行和以 `#` 开头的行被忽略。省市名称需与数据库中的写法一致（如 `北京,北京`）。
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::{ErrorKind, Fallible, PhoneData, PhoneNoInfo};
+
+/// 城市坐标表：`(省, 市)` -> `(纬度, 经度)`
+pub(crate) type GeoTable = HashMap<(String, String), (f64, f64)>;
+
+/// [`PhoneData::find_with_geo`] 的查询结果，序列化时坐标与归属地字段位于同一层
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PhoneNoInfoGeo {
+    /// 归属地信息
+    #[serde(flatten)]
+    pub info: PhoneNoInfo,
+    /// 纬度，扩展数据中没有该城市时为 `None`
+    pub lat: Option<f64>,
+    /// 经度，扩展数据中没有该城市时为 `None`
+    pub lon: Option<f64>,
+}
+
+impl PhoneData {
+    /// 加载 `geo_path` 指向的坐标 CSV，格式见 [`crate::geo`]
+    ///
+    /// 文件中某行的列数不对或坐标无法解析时返回 [`std::io::ErrorKind::InvalidData`]。
+    pub fn with_geo(mut self, geo_path: &str) -> Fallible<PhoneData> {
+        let file = std::fs::File::open(geo_path)?;
+        let geo = parse_geo(BufReader::new(file))?;
+        tracing::info!(
+            "已加载经纬度扩展数据: {}，城市数量: {}",
+            geo_path,
+            geo.len()
+        );
+        self.geo = Arc::new(geo);
+        Ok(self)
+    }
+
+    /// 查询归属地并附加城市坐标，没有坐标数据时 `lat`、`lon` 为 `None`
+    pub fn find_with_geo(&self, no: &str) -> Fallible<PhoneNoInfoGeo> {
+        let info = self.find(no)?;
+        let coordinates = self
+            .geo
+            .get(&(info.province.clone(), info.city.clone()))
+            .copied();
+        Ok(PhoneNoInfoGeo {
+            info,
+            lat: coordinates.map(|(lat, _)| lat),
+            lon: coordinates.map(|(_, lon)| lon),
+        })
+    }
+}
+
+fn parse_geo<R: BufRead>(reader: R) -> Fallible<GeoTable> {
+    let mut geo = GeoTable::new();
+    for (line_no, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty()
+            || line.starts_with('#')
+            || (line_no == 0 && line.starts_with("province"))
+        {
+            continue;
+        }
+        let invalid = || {
+            ErrorKind::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("经纬度数据第 {} 行格式无效: {}", line_no + 1, line),
+            ))
+        };
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let [province, city, lat, lon] = fields[..] else {
+            return Err(invalid());
+        };
+        let lat: f64 = lat.parse().map_err(|_| invalid())?;
+        let lon: f64 = lon.parse().map_err(|_| invalid())?;
+        if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lon) {
+            return Err(invalid());
+        }
+        geo.insert((province.to_string(), city.to_string()), (lat, lon));
+    }
+    Ok(geo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::create_mock_phone_data_with;
+
+    #[test]
+    fn test_find_with_geo() {
+        let path =
+            std::env::temp_dir().join(format!("phone_lookup_geo_{}.csv", std::process::id()));
+        std::fs::write(
+            &path,
+            "province,city,lat,lon\n北京,北京,39.9042,116.4074\n\n# 深圳\n广东,深圳,22.5431,114.0579\n",
+        )
+        .unwrap();
+        let phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380755, "广东|深圳|518000|0755", 1),
+            (1380756, "广东|广州|510000|020", 1),
+        ])
+        .with_geo(path.to_str().unwrap())
+        .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let result = phone_data.find_with_geo("13800138000").unwrap();
+        assert_eq!(result.info, phone_data.find("13800138000").unwrap());
+        assert_eq!((result.lat, result.lon), (Some(39.9042), Some(116.4074)));
+        assert_eq!(
+            phone_data.find_with_geo("13807550000").unwrap().lat,
+            Some(22.5431)
+        );
+
+        // 没有坐标的城市不报错
+        let result = phone_data.find_with_geo("13807560000").unwrap();
+        assert_eq!((result.lat, result.lon), (None, None));
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["city"], "广州");
+        assert!(json["lat"].is_null());
+
+        assert!(matches!(
+            phone_data.find_with_geo("13900000000"),
+            Err(ErrorKind::NotFound)
+        ));
+    }
+
+    #[test]
+    fn test_parse_geo_rejects_invalid_rows() {
+        assert!(parse_geo("北京,北京,39.9\n".as_bytes()).is_err());
+        assert!(parse_geo("北京,北京,abc,116.4\n".as_bytes()).is_err());
+        assert!(parse_geo("北京,北京,116.4,39.9\n".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,127 @@
+//! 索引缓存文件
+//!
+//! 加载 `phone.dat` 时需要逐项解析全部索引。[`PhoneData::dump_index`] 把已排序的索引连同
+//! 数据版本和记录区长度用 bincode 写入单独的文件，[`PhoneData::load_index`] 只读取原文件的
+//! 记录区，索引直接反序列化，两者的版本或记录区长度不一致时拒绝加载。
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read};
+
+use bincode::Options;
+use serde::{Deserialize, Serialize};
+
+use crate::{ErrorKind, Fallible, Index, PhoneData, RecordBytes};
+
+/// 索引缓存文件的内容
+#[derive(Serialize, Deserialize)]
+struct IndexDump {
+    /// 生成缓存时的数据版本
+    version: String,
+    /// 生成缓存时的记录区字节数
+    records_len: u64,
+    index: Vec<Index>,
+}
+
+impl PhoneData {
+    /// 把当前索引写入 `path`，供 [`PhoneData::load_index`] 下次启动时直接加载
+    pub fn dump_index(&self, path: &str) -> Fallible<()> {
+        let data = self.snapshot();
+        let dump = IndexDump {
+            version: data.version.clone(),
+            records_len: data.records.len() as u64,
+            index: data.index.clone(),
+        };
+        let writer = BufWriter::new(File::create(path)?);
+        bincode::DefaultOptions::new()
+            .serialize_into(writer, &dump)
+            .map_err(|e| match *e {
+                bincode::ErrorKind::Io(e) => ErrorKind::Io(e),
+                e => ErrorKind::Io(std::io::Error::other(e)),
+            })
+    }
+
+    /// 从 `records_path` 指向的 `phone.dat` 读取记录区，索引取自 `index_path` 的缓存文件
+    ///
+    /// 缓存文件无法解析，或其中记录的版本、记录区长度与数据文件不一致时返回
+    /// [`ErrorKind::InvalidPhoneDatabase`]，此时应重新加载数据文件并重新生成缓存。
+    pub fn load_index(index_path: &str, records_path: &str) -> Fallible<PhoneData> {
+        let file = File::open(index_path)?;
+        // 以文件长度为反序列化的分配上限，避免损坏的文件导致巨额分配
+        let limit = file.metadata()?.len();
+        let dump: IndexDump = bincode::DefaultOptions::new()
+            .with_limit(limit)
+            .deserialize_from(BufReader::new(file))
+            .map_err(|_| ErrorKind::InvalidPhoneDatabase)?;
+
+        let mut data_file = BufReader::new(File::open(records_path)?);
+        let mut header = [0u8; 8];
+        data_file
+            .read_exact(&mut header)
+            .map_err(|_| ErrorKind::InvalidPhoneDatabase)?;
+        let version =
+            String::from_utf8(header[..4].to_vec()).map_err(|_| ErrorKind::InvalidPhoneDatabase)?;
+        let index_offset = Self::four_u8_to_i32(&header[4..]);
+        if index_offset < 8 {
+            return Err(ErrorKind::InvalidPhoneDatabase);
+        }
+        let expected = index_offset as usize - 8;
+        if version != dump.version || expected as u64 != dump.records_len {
+            tracing::warn!(
+                "索引缓存与数据文件不一致: 缓存版本 {} ({} 字节), 数据文件版本 {} ({} 字节)",
+                dump.version,
+                dump.records_len,
+                version,
+                expected
+            );
+            return Err(ErrorKind::InvalidPhoneDatabase);
+        }
+
+        let mut records = Vec::with_capacity(expected);
+        let actual = data_file.take(expected as u64).read_to_end(&mut records)?;
+        if actual < expected {
+            return Err(ErrorKind::TruncatedRecords { expected, actual });
+        }
+        Ok(Self::from_parts(
+            version,
+            RecordBytes::Owned(records),
+            dump.index,
+            true,
+            1000,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{create_mock_phone_data_with, encode_phone_dat};
+
+    #[test]
+    fn test_index_dump_round_trip() {
+        let dir = std::env::temp_dir();
+        let index_path = dir.join(format!("phone_lookup_index_{}.bin", std::process::id()));
+        let index_path = index_path.to_str().unwrap();
+
+        let phone_data = PhoneData::new().unwrap();
+        phone_data.dump_index(index_path).unwrap();
+        let loaded = PhoneData::load_index(index_path, "phone.dat").unwrap();
+        assert_eq!(loaded.version(), phone_data.version());
+        assert_eq!(loaded.index_count(), phone_data.index_count());
+        for no in ["13800138000", "18086834111", "19200000000"] {
+            assert_eq!(loaded.find(no).unwrap(), phone_data.find(no).unwrap());
+        }
+
+        // 版本相同但记录区不同的数据文件不能复用该缓存
+        let other = create_mock_phone_data_with(&[(1380013, "北京|北京|100000|010", 1)]);
+        let records_path = dir.join(format!("phone_lookup_index_{}.dat", std::process::id()));
+        std::fs::write(&records_path, encode_phone_dat(&other, "2502")).unwrap();
+        let result = PhoneData::load_index(index_path, records_path.to_str().unwrap());
+        std::fs::remove_file(&records_path).unwrap();
+        assert!(matches!(result, Err(ErrorKind::InvalidPhoneDatabase)));
+
+        std::fs::write(index_path, b"not an index").unwrap();
+        let result = PhoneData::load_index(index_path, "phone.dat");
+        std::fs::remove_file(index_path).unwrap();
+        assert!(matches!(result, Err(ErrorKind::InvalidPhoneDatabase)));
+    }
+}
@@ -0,0 +1,204 @@
+//! 数据库完整性检查
+
+use std::collections::HashMap;
+
+use crate::{CardType, ErrorKind, Fallible, PhoneData};
+
+/// 完整性问题类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegrityIssueKind {
+    /// 索引项的记录偏移超出记录区
+    OffsetOutOfRange,
+    /// 记录不是合法的 `省|市|邮编|区号` 格式
+    MalformedRecord,
+    /// 运营商代码无法识别
+    UnknownCardType,
+    /// 前缀没有严格递增，二分查找结果不可靠
+    UnorderedPrefix,
+}
+
+/// 单个完整性问题的位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IntegrityIssue {
+    /// 索引项在索引数组中的位置
+    pub position: usize,
+    /// 索引项的前缀
+    pub prefix: u32,
+    /// 问题类型
+    pub kind: IntegrityIssueKind,
+}
+
+/// 完整性检查报告
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntegrityReport {
+    /// 检查的索引项数量
+    pub checked: usize,
+    /// 记录偏移越界的索引项数量
+    pub offset_out_of_range: usize,
+    /// 记录格式错误的索引项数量
+    pub malformed_records: usize,
+    /// 运营商代码无法识别的索引项数量
+    pub unknown_card_types: usize,
+    /// 前缀未严格递增的索引项数量
+    pub unordered_prefixes: usize,
+    /// 第一个发现的问题
+    pub first_issue: Option<IntegrityIssue>,
+}
+
+/// [`PhoneData::verify`] 返回的检查报告
+pub type VerifyReport = IntegrityReport;
+
+impl IntegrityReport {
+    /// 问题总数
+    pub fn issue_count(&self) -> usize {
+        self.offset_out_of_range
+            + self.malformed_records
+            + self.unknown_card_types
+            + self.unordered_prefixes
+    }
+
+    /// 是否没有发现任何问题
+    pub fn is_clean(&self) -> bool {
+        self.issue_count() == 0
+    }
+
+    fn record(&mut self, position: usize, prefix: i32, kind: IntegrityIssueKind) {
+        match kind {
+            IntegrityIssueKind::OffsetOutOfRange => self.offset_out_of_range += 1,
+            IntegrityIssueKind::MalformedRecord => self.malformed_records += 1,
+            IntegrityIssueKind::UnknownCardType => self.unknown_card_types += 1,
+            IntegrityIssueKind::UnorderedPrefix => self.unordered_prefixes += 1,
+        }
+        self.first_issue.get_or_insert(IntegrityIssue {
+            position,
+            prefix: prefix as u32,
+            kind,
+        });
+    }
+}
+
+impl PhoneData {
+    /// 上线前的整体自检入口，检查内容与 [`PhoneData::verify_integrity`] 相同
+    ///
+    /// 数据库没有任何索引项时返回 [`ErrorKind::InvalidPhoneDatabase`]；
+    /// 其余问题记录在报告中，由调用方通过 [`IntegrityReport::is_clean`] 判断是否可用。
+    pub fn verify(&self) -> Fallible<VerifyReport> {
+        if self.index_count() == 0 {
+            return Err(ErrorKind::InvalidPhoneDatabase);
+        }
+        Ok(self.verify_integrity())
+    }
+
+    /// 检查整个数据库的完整性
+    ///
+    /// 遍历所有索引项，检查记录偏移是否在记录区内、记录能否解析、
+    /// 运营商代码是否合法，以及前缀是否严格递增。每个记录偏移只解析一次。
+    pub fn verify_integrity(&self) -> IntegrityReport {
+        let mut report = IntegrityReport::default();
+        let mut parsed_offsets: HashMap<i32, bool> = HashMap::new();
+        let mut previous_prefix = None;
+
+        let data = self.snapshot();
+        for (position, index_item) in data.index.iter().enumerate() {
+            report.checked += 1;
+            let prefix = index_item.phone_no_prefix;
+
+            if previous_prefix.is_some_and(|previous| prefix <= previous) {
+                report.record(position, prefix, IntegrityIssueKind::UnorderedPrefix);
+            }
+            previous_prefix = Some(prefix);
+
+            let offset = index_item.records_offset;
+            if offset < 8 || offset as usize - 8 >= data.records.len() {
+                report.record(position, prefix, IntegrityIssueKind::OffsetOutOfRange);
+            } else {
+                let valid = *parsed_offsets
+                    .entry(offset)
+                    .or_insert_with(|| self.parse_to_record(&data, offset as usize).is_ok());
+                if !valid {
+                    report.record(position, prefix, IntegrityIssueKind::MalformedRecord);
+                }
+            }
+
+            if CardType::from_u8(index_item.card_type).is_err() {
+                report.record(position, prefix, IntegrityIssueKind::UnknownCardType);
+            }
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{create_mock_phone_data, create_mock_phone_data_with, replace_index};
+
+    #[test]
+    fn test_verify_integrity_clean() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380014, "广东|深圳|518000|0755", 2),
+        ]);
+        let report = phone_data.verify_integrity();
+        assert!(report.is_clean());
+        assert_eq!(report.checked, 2);
+        assert_eq!(report.first_issue, None);
+    }
+
+    #[test]
+    fn test_verify_detects_unordered_prefixes() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380014, "北京|北京|100000|010", 1),
+            (1380015, "北京|北京|100000|010", 1),
+        ]);
+        assert!(phone_data.verify().unwrap().is_clean());
+
+        replace_index(&phone_data, |index| index[2].phone_no_prefix = 1380012);
+        let report = phone_data.verify().unwrap();
+        assert_eq!(report.unordered_prefixes, 1);
+        assert_eq!(report.issue_count(), 1);
+        assert_eq!(
+            report.first_issue,
+            Some(IntegrityIssue {
+                position: 2,
+                prefix: 1380012,
+                kind: IntegrityIssueKind::UnorderedPrefix,
+            })
+        );
+
+        assert!(matches!(
+            create_mock_phone_data().verify(),
+            Err(ErrorKind::InvalidPhoneDatabase)
+        ));
+    }
+
+    #[test]
+    fn test_verify_integrity_detects_issues() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380014, "北京|北京", 2),
+            (1380015, "北京|北京|100000|010", 9),
+        ]);
+        replace_index(&phone_data, |index| {
+            index[2].records_offset = 10_000;
+            index.swap(0, 1);
+        });
+
+        let report = phone_data.verify_integrity();
+        assert_eq!(report.unordered_prefixes, 1);
+        assert_eq!(report.malformed_records, 1);
+        assert_eq!(report.offset_out_of_range, 1);
+        assert_eq!(report.unknown_card_types, 1);
+        assert_eq!(report.issue_count(), 4);
+        assert_eq!(
+            report.first_issue,
+            Some(IntegrityIssue {
+                position: 0,
+                prefix: 1380014,
+                kind: IntegrityIssueKind::MalformedRecord,
+            })
+        );
+    }
+}
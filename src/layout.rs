@@ -0,0 +1,71 @@
+//! 数据文件布局校验
+//!
+//! 只检查文件头和索引区长度，不解析记录。该模块同时被 `build.rs` 通过 `#[path]` 引入，
+//! 因此只能依赖标准库，错误以字符串形式返回。
+
+/// 文件头长度：4 字节版本号 + 4 字节索引区偏移
+pub const HEADER_LEN: usize = 8;
+
+/// 单个索引条目长度：号段 4 字节 + 记录偏移 4 字节 + 运营商 1 字节
+pub const INDEX_ENTRY_LEN: usize = 9;
+
+/// 校验数据文件的基本布局
+///
+/// 要求文件头完整、索引区偏移落在文件范围内，且索引区长度是 9 的整数倍。
+pub fn check_layout(bytes: &[u8]) -> Result<(), String> {
+    if bytes.len() < HEADER_LEN {
+        return Err(format!(
+            "文件长度 {} 字节，不足 {} 字节的文件头",
+            bytes.len(),
+            HEADER_LEN
+        ));
+    }
+    let index_offset = i32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+    if index_offset < HEADER_LEN as i32 || index_offset as usize > bytes.len() {
+        return Err(format!(
+            "索引区偏移 {} 超出文件范围 [{}, {}]",
+            index_offset,
+            HEADER_LEN,
+            bytes.len()
+        ));
+    }
+    let index_len = bytes.len() - index_offset as usize;
+    if !index_len.is_multiple_of(INDEX_ENTRY_LEN) {
+        return Err(format!(
+            "索引区长度 {} 字节不是 {} 的整数倍",
+            index_len, INDEX_ENTRY_LEN
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_database_layout() {
+        let bytes = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/phone.dat")).unwrap();
+        assert!(check_layout(&bytes).is_ok());
+    }
+
+    #[test]
+    fn test_corrupt_layout_rejected() {
+        assert!(check_layout(b"2502").is_err());
+
+        // 索引偏移指向文件之外
+        let mut bytes = b"2502".to_vec();
+        bytes.extend_from_slice(&100i32.to_le_bytes());
+        assert!(check_layout(&bytes).is_err());
+
+        // 索引区多出一个字节
+        let mut bytes = b"2502".to_vec();
+        bytes.extend_from_slice(&12i32.to_le_bytes());
+        bytes.extend_from_slice(b"abc\0");
+        bytes.extend_from_slice(&[0u8; INDEX_ENTRY_LEN + 1]);
+        assert!(check_layout(&bytes).unwrap_err().contains("整数倍"));
+
+        bytes.pop();
+        assert!(check_layout(&bytes).is_ok());
+    }
+}
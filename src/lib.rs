@@ -25,15 +25,32 @@
 
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufReader, Read};
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::io::{BufRead, BufReader, Read};
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
-use std::sync::RwLock;
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
 
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+pub mod analytics;
+#[cfg(feature = "async")]
+mod async_lookup;
+mod cache;
+pub mod compact;
 pub mod config;
+pub mod dataset;
+pub mod etl;
+pub mod geo;
+#[cfg(feature = "index-cache")]
+mod index_cache;
+pub mod integrity;
+#[cfg(test)]
+mod layout;
+#[cfg(feature = "mmap")]
+mod mmap;
 pub mod tauri_commands;
 
 /// 手机号查询相关错误类型
@@ -48,9 +65,31 @@ pub enum ErrorKind {
     /// 在数据库中未找到指定手机号码
     #[error("在数据库中未找到此手机号码")]
     NotFound,
+    /// 手机号码含有非数字字符，`position` 为该字符的位置（从 0 开始）
+    #[error("手机号码第 {position} 位含有无效字符: {found:?}")]
+    InvalidCharacter {
+        /// 字符在输入中的位置
+        position: usize,
+        /// 遇到的字符
+        found: char,
+    },
     /// 运营商代码无效
     #[error("无效的运营商代码")]
     InvalidOpNo,
+    /// 记录区实际长度小于文件头声明的长度（通常是文件下载不完整）
+    #[error("数据库记录区不完整：应为 {expected} 字节，实际读取 {actual} 字节")]
+    TruncatedRecords {
+        /// 文件头声明的记录区字节数
+        expected: usize,
+        /// 实际读取到的字节数
+        actual: usize,
+    },
+    /// 记录分隔符不可用（不能是数字或空字符）
+    #[error("无效的记录分隔符: {0:?}")]
+    InvalidDelimiter(char),
+    /// 缓存大小无效（必须大于0）
+    #[error("缓存大小无效，必须大于0")]
+    InvalidCacheSize,
     /// I/O操作错误
     #[error("I/O 错误: {0}")]
     Io(#[from] std::io::Error),
@@ -61,45 +100,185 @@ pub enum ErrorKind {
 /// 包含手机号归属地数据库的所有信息，支持高性能查询和缓存机制。
 ///
 /// # 特性
-/// - 线程安全：使用 Arc 和 Mutex 确保并发访问安全
+/// - 线程安全：实现 `Send + Sync`，数据与缓存由 Arc 和 RwLock 保护，可以放进 `Arc<PhoneData>` 跨线程共享
 /// - 内存优化：使用 Arc 避免数据重复拷贝
 /// - 缓存支持：内置可配置的 LRU 缓存机制
 #[derive(Debug)]
 pub struct PhoneData {
-    /// 数据库版本信息
-    version: String,
-    /// 记录数据的原始字节数组
-    records: Arc<Vec<u8>>,
-    /// 索引数组，用于二分查找
-    index: Arc<Vec<Index>>,
-    /// LRU 缓存，存储查询结果（使用 RwLock 优化读性能）
-    cache: Arc<RwLock<HashMap<String, PhoneNoInfo>>>,
+    /// 当前的数据库快照，[`PhoneData::reload`] 时整体替换
+    data: Arc<RwLock<Arc<Snapshot>>>,
+    /// LRU 缓存，存储查询结果（使用 RwLock 优化读性能），最大条目数保存在缓存内部
+    cache: Arc<RwLock<cache::ResultCache>>,
     /// 是否启用缓存
     cache_enabled: bool,
-    /// 缓存最大条目数
-    cache_max_size: usize,
-    /// 性能统计：查询总数
+    /// 性能统计，与缓存一样由克隆出的实例共享
+    stats: Arc<QueryStats>,
+    /// 城市字段为空时的回退策略
+    city_fallback: CityFallback,
+    /// 是否在二分查找前拒绝明显不是手机号的输入
+    reject_implausible: bool,
+    /// 是否要求手机号总长度为7-11位，为 `false` 时只要求前7位为数字
+    strict_length: bool,
+    /// 慢查询阈值，`None` 表示不计时
+    slow_query_threshold: Option<Duration>,
+    /// 记录字段分隔符
+    record_delimiter: char,
+    /// 自定义运营商名称，未覆盖的运营商使用内置中文描述
+    carrier_labels: Arc<HashMap<CardType, String>>,
+    /// `find_with_default` 在号段不存在时返回的默认归属地
+    default_region: Option<PhoneNoInfo>,
+    /// 城市坐标，见 [`PhoneData::with_geo`]
+    geo: Arc<geo::GeoTable>,
+    /// 每次 `find` 完成后调用的观察者
+    query_observer: RwLock<Option<QueryObserver>>,
+}
+
+/// 查询性能统计计数器
+#[derive(Debug, Default)]
+struct QueryStats {
+    /// 查询总数
     query_count: AtomicU64,
-    /// 性能统计：缓存命中数
+    /// 缓存命中数
     cache_hits: AtomicU64,
+    /// 从原始字节解析记录的次数（未命中 `Snapshot::records_cache`）
+    record_parses: AtomicU64,
+    /// 是否已经输出过缓存低效警告
+    cache_health_warned: AtomicBool,
+    /// 索引二分查找次数
+    index_searches: AtomicU64,
+    /// 慢查询次数
+    slow_queries: AtomicU64,
+    /// 缓存未命中次数（仅在缓存启用时计数）
+    cache_misses: AtomicU64,
+    /// `find` 返回未找到的次数
+    not_found_count: AtomicU64,
+}
+
+/// 数据库内容的快照：版本、记录区、索引，以及由它们派生、首次使用时才构建的结构
+///
+/// 快照创建后不再替换其中的数据。查询开始时取得当前快照并在整个查询中使用，
+/// 因此 [`PhoneData::reload`] 替换快照时，进行中的查询仍然读取旧数据。
+#[derive(Debug)]
+struct Snapshot {
+    /// 数据库版本信息
+    version: String,
+    /// 记录数据的原始字节，可能来自内存或只读映射的文件
+    records: RecordBytes,
+    /// 索引数组，用于二分查找
+    index: Vec<Index>,
+    /// 按记录偏移索引的驻留字符串记录，首次调用 `find_interned` 时构建
+    interned: OnceLock<HashMap<i32, PhoneNoInfoInterned>>,
+    /// 区号到记录偏移的映射，首次查询固定电话时构建
+    area_codes: OnceLock<HashMap<String, i32>>,
+    /// 省份到号段前缀的反向索引，首次调用 `prefixes_by_province` 时构建
+    province_prefixes: OnceLock<HashMap<String, Vec<u32>>>,
+    /// 城市到索引位置的反向索引，首次调用 `search_by_city` 时构建
+    city_positions: OnceLock<HashMap<String, Vec<usize>>>,
+    /// 按记录偏移缓存的已解析记录，多个号段共用同一条记录时只做一次 UTF-8 转换和拆分
+    ///
+    /// 条目数不超过数据库中的记录数，因此不设上限，也不受结果缓存开关影响。
+    records_cache: RwLock<HashMap<i32, Records>>,
+    /// 按省份缓存的运营商分布，见 `province_carrier_breakdown`
+    carrier_breakdowns: RwLock<HashMap<String, analytics::CarrierBreakdown>>,
+    /// 全库各运营商的号段数量，首次调用 `carrier_distribution` 时统计
+    carrier_distribution: OnceLock<HashMap<CardType, usize>>,
+    /// 全库各省份的号段数量，首次调用 `province_distribution` 时统计
+    province_distribution: OnceLock<HashMap<String, usize>>,
+}
+
+impl Snapshot {
+    fn new(version: String, records: RecordBytes, index: Vec<Index>) -> Self {
+        Snapshot {
+            version,
+            records,
+            index,
+            interned: OnceLock::new(),
+            area_codes: OnceLock::new(),
+            province_prefixes: OnceLock::new(),
+            city_positions: OnceLock::new(),
+            records_cache: RwLock::new(HashMap::new()),
+            carrier_breakdowns: RwLock::new(HashMap::new()),
+            carrier_distribution: OnceLock::new(),
+            province_distribution: OnceLock::new(),
+        }
+    }
+
+    /// 记录偏移处未解析的原始记录（不含结尾的空字符），偏移越界时返回 `None`
+    fn raw_record(&self, offset: i32) -> Option<&[u8]> {
+        let start = usize::try_from(offset).ok()?.checked_sub(8)?;
+        let rest = self.records.get(start..)?;
+        rest.split(|i| *i == 0u8).next()
+    }
+
+    /// 包装为 `PhoneData::data` 字段
+    fn into_shared(self) -> Arc<RwLock<Arc<Snapshot>>> {
+        Arc::new(RwLock::new(Arc::new(self)))
+    }
 }
 
+/// 按已解析的号段查询的函数，参数为原始号码和7位号段，见 `PhoneData::find_with`
+type SegmentSearch<'a> = dyn FnMut(&str, i32) -> Fallible<PhoneNoInfo> + 'a;
+
+/// 查询观察者回调，见 [`PhoneData::set_query_observer`]
+#[derive(Clone)]
+struct QueryObserver(Arc<QueryObserverFn>);
+
+type QueryObserverFn = dyn Fn(&str, &Result<PhoneNoInfo, ErrorKind>) + Send + Sync;
+
+impl std::fmt::Debug for QueryObserver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("QueryObserver")
+    }
+}
+
+/// 克隆出的实例共享数据快照、结果缓存和性能统计
+///
+/// 统计计数与缓存一起共享，否则克隆后各自计数的命中数与共享缓存对不上。
 impl Clone for PhoneData {
     fn clone(&self) -> Self {
         PhoneData {
-            version: self.version.clone(),
-            records: self.records.clone(),
-            index: self.index.clone(),
+            data: self.data.clone(),
             cache: self.cache.clone(),
             cache_enabled: self.cache_enabled,
-            cache_max_size: self.cache_max_size,
-            query_count: AtomicU64::new(self.query_count.load(Ordering::Relaxed)),
-            cache_hits: AtomicU64::new(self.cache_hits.load(Ordering::Relaxed)),
+            stats: self.stats.clone(),
+            city_fallback: self.city_fallback,
+            reject_implausible: self.reject_implausible,
+            strict_length: self.strict_length,
+            slow_query_threshold: self.slow_query_threshold,
+            record_delimiter: self.record_delimiter,
+            carrier_labels: self.carrier_labels.clone(),
+            default_region: self.default_region.clone(),
+            geo: self.geo.clone(),
+            query_observer: RwLock::new(self.query_observer.read().unwrap().clone()),
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// 记录区字节，读入内存或直接映射文件，两者都只读访问
+#[derive(Debug)]
+enum RecordBytes {
+    Owned(Vec<u8>),
+    /// 映射整个文件，`range` 为其中的记录区
+    #[cfg(feature = "mmap")]
+    Mapped {
+        map: memmap2::Mmap,
+        range: std::ops::Range<usize>,
+    },
+}
+
+impl std::ops::Deref for RecordBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            RecordBytes::Owned(bytes) => bytes,
+            #[cfg(feature = "mmap")]
+            RecordBytes::Mapped { map, range } => &map[range.clone()],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 struct Index {
     /// 手机号前七位
     phone_no_prefix: i32,
@@ -133,25 +312,325 @@ struct Records {
     area_code: String,
 }
 
+/// 城市字段为空时的回退策略
+///
+/// 部分号段只分配到省级，记录中的城市为空字符串。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CityFallback {
+    /// 保持空字符串（默认）
+    #[default]
+    None,
+    /// 使用省份名作为城市
+    Province,
+    /// 使用同省内最近的、城市非空的相邻号段的城市
+    NearestPrefix,
+}
+
+/// `PhoneData` 构建器，用于配置默认构造函数未覆盖的可选行为
+///
+/// ```rust
+/// use phone_lookup_rs::{CityFallback, PhoneData};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let phone_data = PhoneData::builder()
+///     .cache_max_size(5000)
+///     .city_fallback(CityFallback::Province)
+///     .build("phone.dat")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PhoneDataBuilder {
+    cache_enabled: bool,
+    cache_max_size: usize,
+    cache_ttl: Option<Duration>,
+    city_fallback: CityFallback,
+    reject_implausible: bool,
+    strict_length: bool,
+    slow_query_threshold: Option<Duration>,
+    record_delimiter: char,
+    carrier_labels: HashMap<CardType, String>,
+    default_region: Option<PhoneNoInfo>,
+    eviction_policy: EvictionPolicy,
+}
+
+impl Default for PhoneDataBuilder {
+    fn default() -> Self {
+        Self {
+            cache_enabled: true,
+            cache_max_size: 1000,
+            cache_ttl: None,
+            city_fallback: CityFallback::None,
+            reject_implausible: false,
+            strict_length: true,
+            slow_query_threshold: None,
+            record_delimiter: DEFAULT_RECORD_DELIMITER,
+            carrier_labels: HashMap::new(),
+            default_region: None,
+            eviction_policy: EvictionPolicy::default(),
+        }
+    }
+}
+
+impl PhoneDataBuilder {
+    /// 创建使用默认配置的构建器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 是否启用结果缓存
+    pub fn cache_enabled(mut self, enabled: bool) -> Self {
+        self.cache_enabled = enabled;
+        self
+    }
+
+    /// 结果缓存的最大条目数
+    pub fn cache_max_size(mut self, max_size: usize) -> Self {
+        self.cache_max_size = max_size;
+        self
+    }
+
+    /// 结果缓存条目的存活时间，默认永不过期
+    ///
+    /// 超过 TTL 的条目命中时视为未命中并重新查询，用于数据库热更新后限制旧结果的存留时间。
+    pub fn cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = Some(ttl);
+        self
+    }
+
+    /// 结果缓存的淘汰策略，默认 LRU
+    pub fn eviction_policy(mut self, policy: EvictionPolicy) -> Self {
+        self.eviction_policy = policy;
+        self
+    }
+
+    /// 城市字段为空时的回退策略
+    pub fn city_fallback(mut self, fallback: CityFallback) -> Self {
+        self.city_fallback = fallback;
+        self
+    }
+
+    /// 是否在二分查找前用 [`is_plausible_mobile`] 快速拒绝不可能存在的号码
+    ///
+    /// 适合大量随机号码的风控类负载，被拒绝的号码直接返回 [`ErrorKind::NotFound`]。
+    pub fn reject_implausible(mut self, enabled: bool) -> Self {
+        self.reject_implausible = enabled;
+        self
+    }
+
+    /// 是否严格限制手机号总长度为7-11位，默认为 `true`
+    ///
+    /// 关闭后超过11位的特殊业务号码或测试号码也按前7位号段查询，前7位仍须为数字。
+    pub fn strict_length(mut self, strict: bool) -> Self {
+        self.strict_length = strict;
+        self
+    }
+
+    /// 慢查询阈值，耗时超过该值的 `find` 会输出一条带脱敏号码的警告日志
+    ///
+    /// 正常查询在微秒级，出现慢查询通常意味着锁竞争等异常。
+    pub fn slow_query_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_query_threshold = Some(threshold);
+        self
+    }
+
+    /// 记录字段分隔符，默认为 `|`
+    ///
+    /// 用于加载重新编码过、使用其他分隔符的数据文件。分隔符不能是数字或空字符。
+    pub fn record_delimiter(mut self, delimiter: char) -> Self {
+        self.record_delimiter = delimiter;
+        self
+    }
+
+    /// 覆盖某个运营商在查询结果中的名称，可多次调用覆盖多个运营商
+    ///
+    /// 用于本地化或白标场景，例如把中国移动显示为 `"China Mobile"`。
+    /// `find_interned` 返回静态字符串，不受该选项影响。
+    pub fn carrier_label(mut self, card_type: CardType, label: impl Into<String>) -> Self {
+        self.carrier_labels.insert(card_type, label.into());
+        self
+    }
+
+    /// [`PhoneData::find_with_default`] 在号段不存在时返回的默认归属地
+    pub fn default_region(mut self, region: PhoneNoInfo) -> Self {
+        self.default_region = Some(region);
+        self
+    }
+
+    /// 从数据库文件构建 `PhoneData`
+    pub fn build(self, path: &str) -> Fallible<PhoneData> {
+        self.validate()?;
+        let mut phone_data =
+            PhoneData::from_file_with_config(path, self.cache_enabled, self.cache_max_size)?;
+        self.apply(&mut phone_data);
+        Ok(phone_data)
+    }
+
+    /// 从内存中的数据库内容构建 `PhoneData`
+    pub fn build_from_bytes(self, bytes: &[u8]) -> Fallible<PhoneData> {
+        self.validate()?;
+        let mut phone_data =
+            PhoneData::from_buf_reader(bytes, self.cache_enabled, self.cache_max_size)?;
+        self.apply(&mut phone_data);
+        Ok(phone_data)
+    }
+
+    /// 校验构建器选项
+    fn validate(&self) -> Fallible<()> {
+        if self.record_delimiter.is_ascii_digit() || self.record_delimiter == '\0' {
+            return Err(ErrorKind::InvalidDelimiter(self.record_delimiter));
+        }
+        Ok(())
+    }
+
+    /// 把构建器中与加载无关的选项应用到已加载的实例
+    fn apply(&self, phone_data: &mut PhoneData) {
+        phone_data.city_fallback = self.city_fallback;
+        phone_data.reject_implausible = self.reject_implausible;
+        phone_data.strict_length = self.strict_length;
+        phone_data.slow_query_threshold = self.slow_query_threshold;
+        phone_data.record_delimiter = self.record_delimiter;
+        phone_data.carrier_labels = Arc::new(self.carrier_labels.clone());
+        phone_data.default_region = self.default_region.clone();
+        phone_data.set_eviction_policy(self.eviction_policy);
+        phone_data.cache.write().unwrap().set_ttl(self.cache_ttl);
+    }
+}
+
 impl PhoneData {
-    /// 获取数据库版本信息
-    pub fn version(&self) -> &str {
-        &self.version
+    /// 创建构建器
+    pub fn builder() -> PhoneDataBuilder {
+        PhoneDataBuilder::new()
+    }
+
+    /// 当前的数据库快照，持有期间不受 [`PhoneData::reload`] 影响
+    fn snapshot(&self) -> Arc<Snapshot> {
+        self.data.read().unwrap().clone()
+    }
+
+    /// `data` 是否仍是当前快照，用于丢弃重新加载之前开始的查询的缓存写入
+    fn is_current(&self, data: &Arc<Snapshot>) -> bool {
+        Arc::ptr_eq(data, &self.data.read().unwrap())
+    }
+
+    /// 从 `path` 重新加载数据库，原子替换当前数据并清空结果缓存
+    ///
+    /// 新文件完整加载成功后才替换，加载失败时返回错误且当前数据不变。进行中的查询继续使用旧快照，
+    /// 替换之后开始的查询使用新数据；查询统计保留，区号映射等惰性结构随新快照重新构建。
+    /// 克隆出的实例共享数据，会一起看到新数据库。
+    pub fn reload(&self, path: &str) -> Fallible<()> {
+        let fresh = Self::from_file_with_config(path, false, 1)?.snapshot();
+        let new_version = fresh.version.clone();
+        // 先取缓存写锁再替换快照，与查询写缓存时的加锁顺序一致，保证替换后缓存中没有旧数据
+        let mut cache = self.cache.write().unwrap();
+        let old = std::mem::replace(&mut *self.data.write().unwrap(), fresh);
+        cache.clear();
+        drop(cache);
+        tracing::info!(
+            "数据库已重新加载: {}，版本 {} -> {}",
+            path,
+            old.version,
+            new_version
+        );
+        Ok(())
+    }
+
+    /// 获取数据库版本信息，[`PhoneData::reload`] 之后返回新数据库的版本
+    pub fn version(&self) -> String {
+        self.snapshot().version.clone()
+    }
+
+    /// 把数据库版本解析为发布日期，无法解析时返回 `None`
+    ///
+    /// `phone.dat` 的版本通常是 `YYMM`（如 `2502` 表示 2025 年 2 月），
+    /// 也接受 `YYYYMM` 和 `YYYYMMDD`；只有年月的版本取当月 1 日。
+    pub fn version_date(&self) -> Option<chrono::NaiveDate> {
+        parse_version_date(&self.snapshot().version)
+    }
+
+    /// 当前数据库的版本日期是否晚于 `other`
+    ///
+    /// 任一方的版本无法解析为日期时返回 `false`，见 [`PhoneData::version_date`]。
+    pub fn is_newer_than(&self, other: &PhoneData) -> bool {
+        match (self.version_date(), other.version_date()) {
+            (Some(this), Some(other)) => this > other,
+            _ => false,
+        }
     }
 
     /// 获取索引记录数量
     pub fn index_count(&self) -> usize {
-        self.index.len()
+        self.snapshot().index.len()
+    }
+
+    /// 获取不重复的前缀数量
+    ///
+    /// 索引按前缀排序，只需比较相邻项。结果小于 [`index_count`](Self::index_count)
+    /// 说明数据中存在重复前缀。
+    pub fn distinct_prefix_count(&self) -> usize {
+        let data = self.snapshot();
+        if data.index.is_empty() {
+            return 0;
+        }
+        1 + data
+            .index
+            .windows(2)
+            .filter(|pair| pair[0].phone_no_prefix != pair[1].phone_no_prefix)
+            .count()
+    }
+
+    /// 找出运营商代码无法识别的号段
+    ///
+    /// 返回 `(前缀, card_type 字节)` 列表。这些号段在 `find` 时会返回
+    /// [`ErrorKind::InvalidOpNo`]，可以在上线新数据前提前发现。只扫描索引，不解析记录。
+    /// 代码 0 表示运营商未知，按 [`CardType::Unknown`] 正常查询，不在此列。
+    pub fn unknown_card_types(&self) -> Vec<(u32, u8)> {
+        self.snapshot()
+            .index
+            .iter()
+            .filter(|idx| CardType::from_u8(idx.card_type).is_err())
+            .map(|idx| (idx.phone_no_prefix as u32, idx.card_type))
+            .collect()
     }
 
     /// 获取查询总数
     pub fn query_count(&self) -> u64 {
-        self.query_count.load(Ordering::Relaxed)
+        self.stats.query_count.load(Ordering::Relaxed)
     }
 
     /// 获取缓存命中数
     pub fn cache_hits(&self) -> u64 {
-        self.cache_hits.load(Ordering::Relaxed)
+        self.stats.cache_hits.load(Ordering::Relaxed)
+    }
+
+    /// 获取索引二分查找次数，可用于衡量缓存和快速拒绝节省的查找
+    pub fn index_searches(&self) -> u64 {
+        self.stats.index_searches.load(Ordering::Relaxed)
+    }
+
+    /// 获取慢查询次数
+    pub fn slow_queries(&self) -> u64 {
+        self.stats.slow_queries.load(Ordering::Relaxed)
+    }
+
+    /// 获取缓存未命中次数
+    pub fn cache_misses(&self) -> u64 {
+        self.stats.cache_misses.load(Ordering::Relaxed)
+    }
+
+    /// 获取 `find` 返回未找到的次数
+    pub fn not_found_count(&self) -> u64 {
+        self.stats.not_found_count.load(Ordering::Relaxed)
+    }
+
+    /// 获取未找到率（百分比），即未找到次数占查询总数的比例
+    pub fn not_found_rate(&self) -> f64 {
+        let total = self.query_count();
+        if total == 0 {
+            0.0
+        } else {
+            (self.not_found_count() as f64 / total as f64) * 100.0
+        }
     }
 
     /// 获取缓存命中率（百分比）
@@ -165,16 +644,93 @@ impl PhoneData {
         }
     }
 
+    /// 根据命中率评估缓存是否有效
+    ///
+    /// 查询数达到 [`CACHE_HEALTH_MIN_QUERIES`] 之前返回 [`CacheHealth::Warming`]；
+    /// 之后命中率低于 [`CACHE_HEALTH_MIN_HIT_RATE`] 视为低效，并且只在第一次判定时输出警告日志，
+    /// 提示调大缓存或直接关闭缓存。
+    pub fn cache_health(&self) -> CacheHealth {
+        if !self.cache_enabled {
+            return CacheHealth::Disabled;
+        }
+        if self.query_count() < CACHE_HEALTH_MIN_QUERIES {
+            return CacheHealth::Warming;
+        }
+
+        let hit_rate = self.cache_hit_rate();
+        if hit_rate >= CACHE_HEALTH_MIN_HIT_RATE {
+            return CacheHealth::Healthy;
+        }
+        if !self.stats.cache_health_warned.swap(true, Ordering::Relaxed) {
+            tracing::warn!(
+                "缓存命中率过低: {:.2}% (查询数: {}, 缓存上限: {})，建议调大缓存或关闭缓存",
+                hit_rate,
+                self.query_count(),
+                self.cache_max_size()
+            );
+        }
+        CacheHealth::Ineffective
+    }
+
     pub fn new() -> Fallible<PhoneData> {
-        let path = get_resource_path("phone.dat");
+        let path = get_resource_path("phone.dat")?;
         tracing::info!("尝试加载数据库文件: {}", path);
         Self::from_file(&path)
     }
 
+    /// 获取进程内共享的数据库实例
+    ///
+    /// 同一路径（按规范化后的绝对路径判断）只会被解析一次，后续调用直接返回缓存的
+    /// `Arc`。加载过程持有全局锁，多个组件同时启动时也不会重复读取文件。
+    pub fn shared(path: &str) -> Fallible<Arc<PhoneData>> {
+        static SHARED: OnceLock<Mutex<HashMap<std::path::PathBuf, Arc<PhoneData>>>> =
+            OnceLock::new();
+
+        let key = std::fs::canonicalize(path)?;
+        let mut shared = SHARED
+            .get_or_init(|| Mutex::new(HashMap::new()))
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if let Some(phone_data) = shared.get(&key) {
+            return Ok(phone_data.clone());
+        }
+
+        let phone_data = Arc::new(Self::from_file(path)?);
+        shared.insert(key, phone_data.clone());
+        Ok(phone_data)
+    }
+
     pub fn from_file(path: &str) -> Fallible<PhoneData> {
         Self::from_file_with_config(path, true, 1000)
     }
 
+    /// 与 [`PhoneData::from_file_with_config`] 相同，并指定结果缓存的淘汰策略和长度校验方式
+    ///
+    /// `strict_length` 的含义见 [`PhoneDataBuilder::strict_length`]。
+    pub fn from_file_with_options(
+        path: &str,
+        cache_enabled: bool,
+        cache_max_size: usize,
+        eviction: EvictionPolicy,
+        strict_length: bool,
+    ) -> Fallible<PhoneData> {
+        let mut phone_data = Self::from_file_with_config(path, cache_enabled, cache_max_size)?;
+        phone_data.set_eviction_policy(eviction);
+        phone_data.strict_length = strict_length;
+        Ok(phone_data)
+    }
+
+    /// 以指定淘汰策略重建空的结果缓存，容量与 TTL 不变，重建后不再与克隆出的实例共享
+    fn set_eviction_policy(&mut self, policy: EvictionPolicy) {
+        let (capacity, ttl) = {
+            let cache = self.cache.read().unwrap();
+            (cache.capacity(), cache.ttl())
+        };
+        let mut cache = cache::ResultCache::with_policy(capacity, policy);
+        cache.set_ttl(ttl);
+        self.cache = Arc::new(RwLock::new(cache));
+    }
+
     pub fn from_file_with_config(
         path: &str,
         cache_enabled: bool,
@@ -182,8 +738,46 @@ impl PhoneData {
     ) -> Fallible<PhoneData> {
         tracing::info!("正在加载手机号码数据库文件: {}", path);
         let data_file = File::open(path)?;
-        let mut data_file = BufReader::new(data_file);
+        Self::from_buf_reader(BufReader::new(data_file), cache_enabled, cache_max_size)
+    }
+
+    /// 从内存中的数据库内容加载，格式与 `phone.dat` 相同
+    pub fn from_bytes(bytes: &[u8]) -> Fallible<PhoneData> {
+        Self::from_buf_reader(bytes, true, 1000)
+    }
+
+    /// 启用 `gzip` 特性时，检测到 gzip 文件头则先解压再解析，否则按原始格式解析
+    fn from_buf_reader<R: BufRead>(
+        #[allow(unused_mut)] mut reader: R,
+        cache_enabled: bool,
+        cache_max_size: usize,
+    ) -> Fallible<PhoneData> {
+        #[cfg(feature = "gzip")]
+        if reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+            tracing::info!("检测到 gzip 压缩的数据文件，加载时解压");
+            return Self::from_reader(
+                flate2::bufread::GzDecoder::new(reader),
+                cache_enabled,
+                cache_max_size,
+            );
+        }
+        Self::from_reader(reader, cache_enabled, cache_max_size)
+    }
+
+    /// 从编译期嵌入的 `phone.dat` 加载，无需在运行时定位数据文件
+    #[cfg(feature = "embed-data")]
+    pub fn embedded() -> Fallible<PhoneData> {
+        Self::from_bytes(include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/phone.dat"
+        )))
+    }
 
+    fn from_reader<R: Read>(
+        mut data_file: R,
+        cache_enabled: bool,
+        cache_max_size: usize,
+    ) -> Fallible<PhoneData> {
         // parse version and index offset
         let mut header_buffer = [0u8; 8];
         data_file
@@ -191,53 +785,220 @@ impl PhoneData {
             .map_err(|_| ErrorKind::InvalidPhoneDatabase)?;
         let version = String::from_utf8(header_buffer[..4].to_vec())
             .map_err(|_| ErrorKind::InvalidPhoneDatabase)?;
-        let index_offset = Self::four_u8_to_i32(&header_buffer[4..]) as u64;
+        let index_offset = Self::four_u8_to_i32(&header_buffer[4..]);
+        if index_offset < 8 {
+            return Err(ErrorKind::InvalidPhoneDatabase);
+        }
 
         // read records
-        let mut records = vec![0u8; index_offset as usize - 8];
-        data_file
-            .read_exact(&mut records)
-            .map_err(|_| ErrorKind::InvalidPhoneDatabase)?;
+        let expected = index_offset as usize - 8;
+        let mut records = Vec::with_capacity(expected);
+        let actual = data_file
+            .by_ref()
+            .take(expected as u64)
+            .read_to_end(&mut records)?;
+        if actual < expected {
+            return Err(ErrorKind::TruncatedRecords { expected, actual });
+        }
 
-        // parse index
-        let mut index = Vec::new();
+        // parse index，读取错误直接返回，末尾不足 9 字节的残余被忽略
+        let mut index_bytes = Vec::new();
+        data_file.read_to_end(&mut index_bytes)?;
         // length of a index is 9
-        let mut index_item = [0u8; 9];
-        loop {
-            match data_file.read_exact(&mut index_item) {
-                Ok(_) => (),
-                Err(e) => {
-                    if e.kind() == std::io::ErrorKind::UnexpectedEof {
-                        break;
-                    }
-                }
-            }
-            let phone_no_prefix = Self::four_u8_to_i32(&index_item[..4]);
-            let records_offset = Self::four_u8_to_i32(&index_item[4..8]);
-            let card_type = index_item[8];
-            index.push(Index {
-                phone_no_prefix,
-                records_offset,
-                card_type,
-            });
-        }
+        let index = index_bytes
+            .chunks_exact(9)
+            .map(Self::parse_index_item)
+            .collect();
 
-        let config = PhoneData {
-            version: version.clone(),
-            records: Arc::new(records),
-            index: Arc::new(index.clone()),
-            cache: Arc::new(RwLock::new(HashMap::new())),
+        Ok(Self::from_parts(
+            version,
+            RecordBytes::Owned(records),
+            index,
             cache_enabled,
             cache_max_size,
-            query_count: AtomicU64::new(0),
-            cache_hits: AtomicU64::new(0),
-        };
+        ))
+    }
+
+    /// 解析一个 9 字节的索引项：号段前缀、记录偏移、卡类型
+    fn parse_index_item(item: &[u8]) -> Index {
+        Index {
+            phone_no_prefix: Self::four_u8_to_i32(&item[..4]),
+            records_offset: Self::four_u8_to_i32(&item[4..8]),
+            card_type: item[8],
+        }
+    }
+
+    /// 由解析好的版本、记录区和索引组装实例，其余选项取默认值
+    fn from_parts(
+        version: String,
+        records: RecordBytes,
+        index: Vec<Index>,
+        cache_enabled: bool,
+        cache_max_size: usize,
+    ) -> PhoneData {
         tracing::info!(
             "数据库加载完成，版本: {}, 索引数量: {}",
             version,
             index.len()
         );
-        Ok(config)
+        PhoneData {
+            data: Snapshot::new(version, records, index).into_shared(),
+            cache: Arc::new(RwLock::new(cache::ResultCache::new(cache_max_size))),
+            cache_enabled,
+            stats: Arc::new(QueryStats::default()),
+            city_fallback: CityFallback::None,
+            reject_implausible: false,
+            strict_length: true,
+            slow_query_threshold: None,
+            record_delimiter: DEFAULT_RECORD_DELIMITER,
+            carrier_labels: Arc::new(HashMap::new()),
+            default_region: None,
+            geo: Arc::new(HashMap::new()),
+            query_observer: RwLock::new(None),
+        }
+    }
+
+    /// 加载数据库并立即做完整性检查，同时返回实例和检查报告
+    ///
+    /// 加载失败时直接返回错误；检查发现的问题不会导致失败，由调用方根据报告决定是否继续使用。
+    pub fn from_file_verified(
+        path: &str,
+        cache_enabled: bool,
+        cache_max_size: usize,
+    ) -> Fallible<(PhoneData, integrity::IntegrityReport)> {
+        let phone_data = Self::from_file_with_config(path, cache_enabled, cache_max_size)?;
+        let report = phone_data.verify_integrity();
+        if !report.is_clean() {
+            tracing::warn!(
+                "数据库完整性检查发现 {} 个问题: {:?}",
+                report.issue_count(),
+                report
+            );
+        }
+        Ok((phone_data, report))
+    }
+
+    /// 依次加载多个数据库文件并合并为一个实例，用于在主库之外叠加号段补丁
+    ///
+    /// 相同号段前缀以后加载的文件为准，合并后的索引按前缀重新排序。各文件的记录区依次拼接，
+    /// 索引中的记录偏移相应平移。版本号取最后一个文件的版本，缓存设置与 [`PhoneData::from_file`] 相同。
+    /// 未指定任何文件时返回 I/O 错误，合并后的记录区超出 `i32` 偏移范围时返回
+    /// [`ErrorKind::InvalidPhoneDatabase`]。
+    pub fn from_files(paths: &[&str]) -> Fallible<PhoneData> {
+        if paths.is_empty() {
+            return Err(ErrorKind::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "未指定数据库文件",
+            )));
+        }
+
+        let mut version = String::new();
+        let mut records = Vec::new();
+        let mut index: std::collections::BTreeMap<i32, Index> = std::collections::BTreeMap::new();
+        for path in paths {
+            let part = Self::from_file_with_config(path, false, 1)?;
+            // 原偏移相对于本文件开头，拼接后整体后移已合并的记录区长度
+            let shift =
+                i32::try_from(records.len()).map_err(|_| ErrorKind::InvalidPhoneDatabase)?;
+            let part = part.snapshot();
+            records.extend_from_slice(&part.records);
+            for item in part.index.iter() {
+                let records_offset = item
+                    .records_offset
+                    .checked_add(shift)
+                    .ok_or(ErrorKind::InvalidPhoneDatabase)?;
+                index.insert(
+                    item.phone_no_prefix,
+                    Index {
+                        records_offset,
+                        ..*item
+                    },
+                );
+            }
+            version = part.version.clone();
+        }
+        i32::try_from(records.len() + 8).map_err(|_| ErrorKind::InvalidPhoneDatabase)?;
+
+        tracing::info!("已合并 {} 个数据库文件", paths.len());
+        Ok(Self::from_parts(
+            version,
+            RecordBytes::Owned(records),
+            index.into_values().collect(),
+            true,
+            1000,
+        ))
+    }
+
+    /// 构建只包含某个省份号段的新实例，用于只服务单一地区、内存受限的部署
+    ///
+    /// 新实例只保留该省份的索引条目和被引用的记录，沿用当前实例的配置，缓存和统计从零开始。
+    /// 记录无法解析或运营商代码无效的号段会被丢弃，因此结果总能通过 `verify_integrity`；
+    /// 运营商未知（[`CardType::Unknown`]）的号段照常保留。
+    /// 省份不存在时返回 [`ErrorKind::NotFound`]。
+    pub fn subset_by_province(&self, province: &str) -> Fallible<PhoneData> {
+        let data = self.snapshot();
+        let mut records = Vec::new();
+        // 原记录偏移 -> 新记录偏移，不属于该省份的记录为 None
+        let mut offsets: HashMap<i32, Option<i32>> = HashMap::new();
+        let mut index = Vec::new();
+        for index_item in data.index.iter() {
+            if CardType::from_u8(index_item.card_type).is_err() {
+                continue;
+            }
+            let new_offset = *offsets.entry(index_item.records_offset).or_insert_with(|| {
+                let raw = data.raw_record(index_item.records_offset)?;
+                let record = self
+                    .parse_to_record(&data, index_item.records_offset as usize)
+                    .ok()?;
+                if record.province != province {
+                    return None;
+                }
+                let new_offset = (records.len() + 8) as i32;
+                records.extend_from_slice(raw);
+                records.push(0);
+                Some(new_offset)
+            });
+            if let Some(new_offset) = new_offset {
+                index.push((index_item.phone_no_prefix, new_offset, index_item.card_type));
+            }
+        }
+        if index.is_empty() {
+            return Err(ErrorKind::NotFound);
+        }
+
+        let mut bytes = Vec::with_capacity(8 + records.len() + index.len() * 9);
+        bytes.extend_from_slice(data.version.as_bytes());
+        bytes.extend_from_slice(&((records.len() + 8) as i32).to_le_bytes());
+        bytes.extend_from_slice(&records);
+        for (prefix, offset, card_type) in index {
+            bytes.extend_from_slice(&prefix.to_le_bytes());
+            bytes.extend_from_slice(&offset.to_le_bytes());
+            bytes.push(card_type);
+        }
+
+        let mut subset = Self::from_reader(&bytes[..], self.cache_enabled, self.cache_max_size())?;
+        subset.city_fallback = self.city_fallback;
+        subset.reject_implausible = self.reject_implausible;
+        subset.strict_length = self.strict_length;
+        subset.slow_query_threshold = self.slow_query_threshold;
+        subset.record_delimiter = self.record_delimiter;
+        subset.carrier_labels = self.carrier_labels.clone();
+        subset.default_region = self.default_region.clone();
+        subset.geo = self.geo.clone();
+        subset.set_eviction_policy(self.cache.read().unwrap().policy());
+        subset
+            .cache
+            .write()
+            .unwrap()
+            .set_ttl(self.cache.read().unwrap().ttl());
+        let subset_data = subset.snapshot();
+        tracing::info!(
+            "已构建省份子集: {}，索引数量: {}，记录区 {} 字节",
+            province,
+            subset_data.index.len(),
+            subset_data.records.len()
+        );
+        Ok(subset)
     }
 
     #[inline]
@@ -251,11 +1012,30 @@ impl PhoneData {
         }
     }
 
-    fn parse_to_record(&self, offset: usize) -> Fallible<Records> {
-        if let Some(record) = self.records[offset - 8..].splitn(2, |i| *i == 0u8).nth(0) {
+    fn parse_to_record(&self, data: &Snapshot, offset: usize) -> Fallible<Records> {
+        // 偏移来自索引区，损坏的文件可能指向记录区之外
+        if offset < 8 || offset - 8 >= data.records.len() {
+            return Err(ErrorKind::InvalidPhoneDatabase);
+        }
+        let key = offset as i32;
+        if let Some(record) = data.records_cache.read().unwrap().get(&key) {
+            return Ok(record.clone());
+        }
+        let record = self.decode_record(data, offset)?;
+        data.records_cache
+            .write()
+            .unwrap()
+            .insert(key, record.clone());
+        Ok(record)
+    }
+
+    /// 从记录区的原始字节解析 `offset` 处的记录，不经过 `records_cache`
+    fn decode_record(&self, data: &Snapshot, offset: usize) -> Fallible<Records> {
+        self.stats.record_parses.fetch_add(1, Ordering::Relaxed);
+        if let Some(record) = data.records[offset - 8..].splitn(2, |i| *i == 0u8).nth(0) {
             let record =
                 String::from_utf8(record.to_vec()).map_err(|_| ErrorKind::InvalidPhoneDatabase)?;
-            let record: Vec<&str> = record.split('|').collect();
+            let record: Vec<&str> = record.split(self.record_delimiter).collect();
             if record.len() != 4 {
                 return Err(ErrorKind::InvalidPhoneDatabase);
             }
@@ -271,76 +1051,973 @@ impl PhoneData {
     }
 
     /// 优化的二分查找算法查找 `phone_no` 数据
+    ///
+    /// 先用 `parse_phone_prefix` 解析出前7位号段，再做与 [`PhoneData::find_by_prefix`]
+    /// 相同的号段查找；结果以完整号码为键缓存。
     pub fn find(&self, no: &str) -> Fallible<PhoneNoInfo> {
-        // 增加查询计数
-        self.query_count.fetch_add(1, Ordering::Relaxed);
+        self.find_with(no, &mut |no, prefix| self.lookup_parsed(no, prefix))
+    }
 
-        let len = no.len();
-        if !(7..=11).contains(&len) {
-            return Err(ErrorKind::InvalidLength);
-        }
+    /// `find` 的完整流程（统计、慢查询、观察者），号段的二分查找交给 `search`
+    fn find_with(&self, no: &str, search: &mut SegmentSearch<'_>) -> Fallible<PhoneNoInfo> {
+        self.observed(no, || self.lookup(no, search))
+    }
 
-        // 检查缓存（仅当缓存启用时）使用读锁优化性能
-        if self.cache_enabled {
-            if let Ok(cache) = self.cache.read() {
-                if let Some(cached_result) = cache.get(no) {
-                    // 增加缓存命中计数
-                    self.cache_hits.fetch_add(1, Ordering::Relaxed);
-                    tracing::debug!("从缓存返回手机号 {} 的信息", no);
-                    return Ok(cached_result.clone());
+    /// 执行一次查询并记录慢查询、未找到次数，最后通知观察者；`no` 是日志和观察者看到的号码
+    fn observed(
+        &self,
+        no: &str,
+        query: impl FnOnce() -> Fallible<PhoneNoInfo>,
+    ) -> Fallible<PhoneNoInfo> {
+        let result = match self.slow_query_threshold {
+            None => query(),
+            Some(threshold) => {
+                let start = Instant::now();
+                let result = query();
+                let elapsed = start.elapsed();
+                if elapsed >= threshold {
+                    self.stats.slow_queries.fetch_add(1, Ordering::Relaxed);
+                    tracing::warn!(
+                        "慢查询: {} 耗时 {:?}（阈值 {:?}）",
+                        mask_number(no),
+                        elapsed,
+                        threshold
+                    );
                 }
+                result
             }
+        };
+
+        if matches!(result, Err(ErrorKind::NotFound)) {
+            self.stats.not_found_count.fetch_add(1, Ordering::Relaxed);
         }
 
-        // 快速解析前7位数字，避免字符串转换
+        // 先复制观察者再释放锁，避免观察者内部重新设置观察者时死锁
+        let observer = self.query_observer.read().unwrap().clone();
+        if let Some(observer) = observer {
+            (observer.0)(no, &result);
+        }
+        result
+    }
+
+    /// 先用 [`normalize_number`] 规范化输入（如 `+86 138-0013-8000`），再调用 [`PhoneData::find`]
+    ///
+    /// 输入含有无法识别的字符（包括不在开头或不是 `+86` 的加号）时返回
+    /// [`ErrorKind::InvalidCharacter`]，指出第一个这样的字符；
+    /// 去掉国家码和分隔符后为空或长度不符合要求（见 [`PhoneDataBuilder::strict_length`]）时
+    /// 返回 [`ErrorKind::InvalidLength`]。
+    pub fn find_normalized(&self, input: &str) -> Fallible<PhoneNoInfo> {
+        let no = normalize_number(input).ok_or_else(|| normalize_error(input))?;
+        self.check_length(&no)?;
+        self.find(&no)
+    }
+
+    /// 设置查询观察者，每次 `find` 完成后以输入号码和查询结果调用
+    ///
+    /// 适合计费、审计等需要跟随每次查询执行的逻辑。观察者在所有缓存锁释放后调用，
+    /// 可以在其中安全地再次查询。再次设置会替换之前的观察者。
+    pub fn set_query_observer(
+        &self,
+        f: impl Fn(&str, &Result<PhoneNoInfo, ErrorKind>) + Send + Sync + 'static,
+    ) {
+        *self.query_observer.write().unwrap() = Some(QueryObserver(Arc::new(f)));
+    }
+
+    /// 移除查询观察者
+    pub fn clear_query_observer(&self) {
+        *self.query_observer.write().unwrap() = None;
+    }
+
+    /// 与 [`PhoneData::find`] 结果相同的纯查询：不读写结果缓存，不计入任何统计，也不通知观察者
+    ///
+    /// 适合自检、预热、校验等不应影响 `query_count`、`cache_hits` 等业务指标的场景。
+    pub fn find_quiet(&self, no: &str) -> Fallible<PhoneNoInfo> {
+        let data = self.snapshot();
+        self.lookup_core(no, &mut |_, prefix| self.search_segment(&data, prefix))
+    }
+
+    /// 不分配内存的查询，省、市等字段直接借用数据库记录区，见 [`PhoneNoInfoRef`]
+    ///
+    /// 结果缓存保存的是 owned 结果，因此该方法不读写缓存，每次都从记录区定位字段；
+    /// 只计入查询总数和二分查找次数，不通知观察者。不支持固定电话。
+    /// 城市回退为 [`CityFallback::NearestPrefix`] 且城市为空时，查找相邻号段仍会解析记录。
+    pub fn find_ref(&self, no: &str) -> Fallible<PhoneNoInfoRef<'_>> {
+        self.stats.query_count.fetch_add(1, Ordering::Relaxed);
+        self.check_length(no)?;
         let no_parsed = self.parse_phone_prefix(no)?;
+        if self.is_implausible(no) {
+            return Err(ErrorKind::NotFound);
+        }
 
-        // 使用标准库的二分查找，性能更优
-        match self
+        self.stats.index_searches.fetch_add(1, Ordering::Relaxed);
+        let data = self.snapshot();
+        let pos = data
             .index
             .binary_search_by_key(&no_parsed, |idx| idx.phone_no_prefix)
-        {
-            Ok(pos) => {
-                let index_item = &self.index[pos];
-                let record = self.parse_to_record(index_item.records_offset as usize)?;
-                let card_type = CardType::from_u8(index_item.card_type)?;
-                let result = PhoneNoInfo {
-                    province: record.province,
-                    city: record.city,
-                    zip_code: record.zip_code,
-                    area_code: record.area_code,
-                    card_type: card_type.get_description().to_string(),
-                };
-
-                // 缓存结果（优化锁粒度：最小化写锁持有时间）
-                if self.cache_enabled {
-                    // 首先用读锁快速检查缓存大小，避免不必要的写锁获取
-                    let needs_cleanup = if let Ok(cache) = self.cache.read() {
-                        cache.len() >= self.cache_max_size
-                    } else {
-                        false
-                    };
-
-                    if let Ok(mut cache) = self.cache.write() {
-                        // 双重检查：可能在获取写锁期间其他线程已更新缓存
-                        if !cache.contains_key(no) {
-                            if needs_cleanup && cache.len() >= self.cache_max_size {
-                                // 优化的LRU清理：收集一半的keys后立即释放迭代器
-                                let keys_to_remove: Vec<String> =
-                                    cache.keys().take(cache.len() / 2).cloned().collect();
-                                for key in keys_to_remove {
-                                    cache.remove(&key);
-                                }
-                                tracing::debug!("缓存已满，清理后插入新条目");
-                            }
-                            cache.insert(no.to_string(), result.clone());
-                        }
+            .map_err(|_| ErrorKind::NotFound)?;
+        let index_item = data.index[pos];
+        let [province, mut city, zip_code, area_code] =
+            self.record_ranges(&data, index_item.records_offset as usize)?;
+        if city.is_empty() {
+            match self.city_fallback {
+                CityFallback::None => {}
+                CityFallback::Province => city = province.clone(),
+                CityFallback::NearestPrefix => {
+                    let own_province = record_field(&data, &province);
+                    if let Some(i) = self.nearest_city_neighbor(&data, pos, own_province) {
+                        let [_, neighbor_city, _, _] =
+                            self.record_ranges(&data, data.index[i].records_offset as usize)?;
+                        city = neighbor_city;
                     }
                 }
-
-                Ok(result)
             }
-            Err(_) => Err(ErrorKind::NotFound),
+        }
+        let carrier = CardType::from_u8(index_item.card_type)?;
+        Ok(PhoneNoInfoRef {
+            province,
+            city,
+            zip_code,
+            area_code,
+            card_type: self.carrier_label(carrier),
+            carrier,
+            data,
+        })
+    }
+
+    /// 定位 `offset` 处记录的省、市、邮编、区号在记录区中的字节范围，不分配内存
+    fn record_ranges(&self, data: &Snapshot, offset: usize) -> Fallible<[Range<usize>; 4]> {
+        if offset < 8 || offset - 8 >= data.records.len() {
+            return Err(ErrorKind::InvalidPhoneDatabase);
+        }
+        let start = offset - 8;
+        let bytes = &data.records[start..];
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        let record =
+            std::str::from_utf8(&bytes[..end]).map_err(|_| ErrorKind::InvalidPhoneDatabase)?;
+
+        let mut ranges = [0..0, 0..0, 0..0, 0..0];
+        let mut fields = record.split(self.record_delimiter);
+        let mut field_start = start;
+        for range in ranges.iter_mut() {
+            let field = fields.next().ok_or(ErrorKind::InvalidPhoneDatabase)?;
+            *range = field_start..field_start + field.len();
+            field_start = range.end + self.record_delimiter.len_utf8();
+        }
+        if fields.next().is_some() {
+            return Err(ErrorKind::InvalidPhoneDatabase);
+        }
+        Ok(ranges)
+    }
+
+    /// `find` 的实际查询逻辑：在核心流程上加查询计数与结果缓存
+    fn lookup(&self, no: &str, search: &mut SegmentSearch<'_>) -> Fallible<PhoneNoInfo> {
+        // 增加查询计数
+        self.stats.query_count.fetch_add(1, Ordering::Relaxed);
+
+        self.lookup_core(no, &mut |no, prefix| match self.cached(no) {
+            Some(cached_result) => Ok(cached_result),
+            None => search(no, prefix),
+        })
+    }
+
+    /// 查询的核心流程：校验号码，固定电话按区号查询，手机号解析出7位号段后交给 `search`
+    ///
+    /// 本身不读写缓存、不计入统计，这些由调用方在 `search` 中决定。
+    fn lookup_core(&self, no: &str, search: &mut SegmentSearch<'_>) -> Fallible<PhoneNoInfo> {
+        // 以 0 开头的是带区号的固定电话，按区号查询
+        if no.starts_with('0') {
+            return self.find_landline(no);
+        }
+
+        self.check_length(no)?;
+
+        // 快速解析前7位数字，避免字符串转换
+        let no_parsed = self.parse_phone_prefix(no)?;
+
+        if self.is_implausible(no) {
+            tracing::debug!("号码 {} 不符合手机号特征，跳过查找", no);
+            return Err(ErrorKind::NotFound);
+        }
+
+        search(no, no_parsed)
+    }
+
+    /// 按已解析的7位号段查询，跳过字符串解析，是开销最低的查询入口
+    ///
+    /// 结果缓存以完整号码为键，因此该方法不读写缓存，每次都直接二分查找。
+    /// 号段不是7位数时返回 [`ErrorKind::InvalidLength`]。
+    /// 与 `find` 一样计入查询统计和慢查询日志并通知观察者，观察者收到的号码是7位号段字符串。
+    pub fn find_by_prefix(&self, prefix: u32) -> Fallible<PhoneNoInfo> {
+        let key = prefix.to_string();
+        self.observed(&key, || {
+            self.stats.query_count.fetch_add(1, Ordering::Relaxed);
+            if !(1_000_000..=9_999_999).contains(&prefix) {
+                return Err(ErrorKind::InvalidLength);
+            }
+            self.search_prefix(&self.snapshot(), prefix as i32)
+        })
+    }
+
+    /// 查询号码，号段不存在时返回构建器中配置的默认归属地
+    ///
+    /// 只有 [`ErrorKind::NotFound`] 会被替换为默认值，号码格式错误等其他错误照常返回；
+    /// 未配置默认归属地时与 `find` 相同。
+    pub fn find_with_default(&self, no: &str) -> Fallible<PhoneNoInfo> {
+        match (self.find(no), &self.default_region) {
+            (Err(ErrorKind::NotFound), Some(region)) => Ok(region.clone()),
+            (result, _) => result,
+        }
+    }
+
+    /// 查询号码并按指定语言返回描述文字
+    ///
+    /// [`Locale::Zh`] 与 `find` 相同；[`Locale::En`] 把运营商替换为英文名称，见 [`Locale`]。
+    pub fn find_localized(&self, no: &str, locale: Locale) -> Fallible<PhoneNoInfo> {
+        let mut info = self.find(no)?;
+        if locale == Locale::En {
+            if let Some(card_type) = CardType::from_icon_key(&info.icon_key) {
+                info.card_type = card_type.as_str_en().to_string();
+            } else if info.icon_key == LANDLINE_ICON_KEY {
+                info.card_type = LANDLINE_CARD_TYPE_EN.to_string();
+            }
+        }
+        Ok(info)
+    }
+
+    /// 检查缓存（仅当缓存启用时）
+    ///
+    /// 命中判断在读锁下完成，只有命中的条目不是最近使用的条目时才取写锁调整访问顺序。
+    fn cached(&self, key: &str) -> Option<PhoneNoInfo> {
+        if !self.cache_enabled {
+            return None;
+        }
+        let (cached_result, needs_touch) = {
+            let cache = self.cache.read().ok()?;
+            let Some((info, needs_touch)) = cache.peek(key) else {
+                self.stats.cache_misses.fetch_add(1, Ordering::Relaxed);
+                return None;
+            };
+            (info.clone(), needs_touch)
+        };
+        if needs_touch {
+            if let Ok(mut cache) = self.cache.write() {
+                cache.touch(key);
+            }
+        }
+        // 增加缓存命中计数
+        self.stats.cache_hits.fetch_add(1, Ordering::Relaxed);
+        tracing::debug!("从缓存返回手机号 {} 的信息", key);
+        Some(cached_result)
+    }
+
+    /// 预先查询一批号码并写入结果缓存，返回新写入的条目数
+    ///
+    /// 不计入查询统计；缓存未启用时直接返回 0。无效、未找到或已缓存的号码被跳过，
+    /// 固定电话不参与预热。号码多于缓存容量时，较早写入的条目会按淘汰策略被挤出。
+    pub fn warm_up(&self, numbers: &[&str]) -> usize {
+        if !self.cache_enabled {
+            return 0;
+        }
+        numbers.iter().filter(|no| self.warm_up_one(no)).count()
+    }
+
+    /// 按索引顺序为每个号段查询代表号码（前缀加 `0000`）并写入结果缓存，缓存写满即停止
+    ///
+    /// 返回新写入的条目数，不计入查询统计；缓存未启用时直接返回 0。
+    pub fn warm_up_all_segments(&self) -> usize {
+        if !self.cache_enabled {
+            return 0;
+        }
+        let capacity = self.cache_max_size();
+        let mut warmed = 0;
+        for item in self.snapshot().index.iter() {
+            if self.cache.read().unwrap().len() >= capacity {
+                break;
+            }
+            if self.warm_up_one(&format!("{}0000", item.phone_no_prefix)) {
+                warmed += 1;
+            }
+        }
+        tracing::info!("结果缓存预热完成，写入 {} 个号段", warmed);
+        warmed
+    }
+
+    /// 查询单个手机号并写入缓存，跳过查询统计，返回是否新写入了条目
+    fn warm_up_one(&self, no: &str) -> bool {
+        if no.starts_with('0') || self.check_length(no).is_err() {
+            return false;
+        }
+        if self.cache.read().unwrap().contains_key(no) {
+            return false;
+        }
+        let Ok(prefix) = self.parse_phone_prefix(no) else {
+            return false;
+        };
+        let data = self.snapshot();
+        let Ok(info) = self.search_segment(&data, prefix) else {
+            return false;
+        };
+        let mut cache = self.cache.write().unwrap();
+        if cache.contains_key(no) || !self.is_current(&data) {
+            return false;
+        }
+        cache.insert(no.to_string(), info);
+        true
+    }
+
+    /// 二分查找已解析的号段，并以 `no` 为键缓存结果
+    fn lookup_parsed(&self, no: &str, no_parsed: i32) -> Fallible<PhoneNoInfo> {
+        let data = self.snapshot();
+        let result = self.search_prefix(&data, no_parsed)?;
+        self.cache_result(&data, no, &result);
+        Ok(result)
+    }
+
+    /// 以 `no` 为键缓存从快照 `data` 查到的结果，已满时只淘汰最久未使用的条目
+    fn cache_result(&self, data: &Arc<Snapshot>, no: &str, result: &PhoneNoInfo) {
+        if !self.cache_enabled {
+            return;
+        }
+        if let Ok(mut cache) = self.cache.write() {
+            // 双重检查：可能在获取写锁期间其他线程已更新缓存；
+            // 查询期间数据库被重新加载时，旧快照的结果不再写入缓存
+            if !cache.contains_key(no)
+                && self.is_current(data)
+                && cache.insert(no.to_string(), result.clone()) > 0
+            {
+                tracing::debug!("缓存已满，淘汰最久未使用的条目");
+            }
+        }
+    }
+
+    /// `find` 与 `find_by_prefix` 共用的号段查找，计入二分查找次数但不读写结果缓存
+    fn search_prefix(&self, data: &Snapshot, no_parsed: i32) -> Fallible<PhoneNoInfo> {
+        self.stats.index_searches.fetch_add(1, Ordering::Relaxed);
+        self.search_segment(data, no_parsed)
+    }
+
+    /// 在快照中二分查找7位号段并组装结果，不读写结果缓存，也不计入任何统计
+    fn search_segment(&self, data: &Snapshot, no_parsed: i32) -> Fallible<PhoneNoInfo> {
+        // 使用标准库的二分查找，性能更优
+        let pos = data
+            .index
+            .binary_search_by_key(&no_parsed, |idx| idx.phone_no_prefix)
+            .map_err(|_| ErrorKind::NotFound)?;
+        self.info_at(data, pos)
+    }
+
+    /// 替换全部自定义运营商名称，未出现在 `labels` 中的运营商回退到内置中文描述
+    ///
+    /// 效果与构建器的 [`PhoneDataBuilder::carrier_label`] 相同，例如把中国移动显示为 `"移动"`。
+    /// 缓存中的结果使用旧名称，而克隆出的实例仍会按旧名称写入共享缓存，
+    /// 因此改用容量、TTL 和淘汰策略相同的独立空缓存，之后不再与其他克隆共享结果缓存。
+    pub fn set_carrier_labels(&mut self, labels: HashMap<CardType, String>) {
+        self.carrier_labels = Arc::new(labels);
+        let policy = self.cache.read().unwrap().policy();
+        self.set_eviction_policy(policy);
+    }
+
+    /// 运营商在查询结果中的名称，优先使用自定义名称
+    fn carrier_label(&self, card_type: CardType) -> &str {
+        self.carrier_labels
+            .get(&card_type)
+            .map(String::as_str)
+            .unwrap_or_else(|| card_type.get_description())
+    }
+
+    /// 校验手机号长度：至少7位，`strict_length` 为 `true` 时最多11位
+    fn check_length(&self, no: &str) -> Fallible<()> {
+        let len = no.len();
+        if len < 7 || (self.strict_length && len > 11) {
+            return Err(ErrorKind::InvalidLength);
+        }
+        Ok(())
+    }
+
+    /// 开启 `reject_implausible` 且号码不符合手机号特征时返回 `true`，长度由 `check_length` 校验
+    fn is_implausible(&self, no: &str) -> bool {
+        self.reject_implausible && !has_mobile_shape(no.as_bytes())
+    }
+
+    /// 校验号码并在快照中二分查找其所在的索引位置，不经过结果缓存
+    fn index_position(&self, data: &Snapshot, no: &str) -> Fallible<usize> {
+        self.check_length(no)?;
+        let no_parsed = self.parse_phone_prefix(no)?;
+        self.stats.index_searches.fetch_add(1, Ordering::Relaxed);
+        data.index
+            .binary_search_by_key(&no_parsed, |idx| idx.phone_no_prefix)
+            .map_err(|_| ErrorKind::NotFound)
+    }
+
+    /// 查询归属地，并返回命中的7位号段前缀作为号段标识（如 `1380013`）
+    ///
+    /// 索引按号段精确匹配，返回的号段恒等于号码的前7位；号段不在库中时返回 [`ErrorKind::NotFound`]，
+    /// 不会退而返回相邻号段。不经过结果缓存，也不计入查询统计。
+    pub fn find_with_segment(&self, no: &str) -> Fallible<(PhoneNoInfo, u32)> {
+        let data = self.snapshot();
+        let pos = self.index_position(&data, no)?;
+        Ok((
+            self.info_at(&data, pos)?,
+            data.index[pos].phone_no_prefix as u32,
+        ))
+    }
+
+    /// 只查询号码所属省份
+    ///
+    /// 解析记录时读到第一个分隔符即停止，不拆分城市、邮编和区号，适合只关心省份的批量任务。
+    /// 不经过结果缓存，也不计入查询统计。
+    pub fn find_province(&self, no: &str) -> Fallible<String> {
+        let data = self.snapshot();
+        let pos = self.index_position(&data, no)?;
+        let record = data
+            .raw_record(data.index[pos].records_offset)
+            .ok_or(ErrorKind::InvalidPhoneDatabase)?;
+        let record = std::str::from_utf8(record).map_err(|_| ErrorKind::InvalidPhoneDatabase)?;
+        record
+            .split_once(self.record_delimiter)
+            .map(|(province, _)| province.to_string())
+            .ok_or(ErrorKind::InvalidPhoneDatabase)
+    }
+
+    /// 只返回号码的长途区号，如 `"010"`
+    ///
+    /// 经过 [`PhoneData::find`]，共享结果缓存。记录中区号为空时返回 [`ErrorKind::NotFound`]。
+    pub fn find_area_code(&self, no: &str) -> Fallible<String> {
+        non_empty(self.find(no)?.area_code)
+    }
+
+    /// 只返回号码的邮政编码，如 `"100000"`
+    ///
+    /// 经过 [`PhoneData::find`]，共享结果缓存。记录中邮编为空时返回 [`ErrorKind::NotFound`]。
+    pub fn find_zip_code(&self, no: &str) -> Fallible<String> {
+        non_empty(self.find(no)?.zip_code)
+    }
+
+    /// 解析指定索引位置的归属地信息
+    fn info_at(&self, data: &Snapshot, pos: usize) -> Fallible<PhoneNoInfo> {
+        let index_item = &data.index[pos];
+        let mut record = self.parse_to_record(data, index_item.records_offset as usize)?;
+        if record.city.is_empty() {
+            self.apply_city_fallback(data, pos, &mut record);
+        }
+        let card_type = CardType::from_u8(index_item.card_type)?;
+        Ok(PhoneNoInfo {
+            province: record.province,
+            city: record.city,
+            zip_code: record.zip_code,
+            area_code: record.area_code,
+            card_type: self.carrier_label(card_type).to_string(),
+            icon_key: card_type.icon_key().to_string(),
+            #[cfg(feature = "timezone")]
+            timezone: TIMEZONE.to_string(),
+        })
+    }
+
+    /// 查询号码所在的索引条目及其前后各 `window` 个相邻条目，用于人工核对数据
+    ///
+    /// 返回按号段升序排列的 `(号段, 归属地)` 列表，靠近索引两端时窗口会被截断。
+    /// 不经过结果缓存，也不计入查询统计。窗口内任一条目解析失败时返回对应错误。
+    pub fn find_neighbors(&self, no: &str, window: usize) -> Fallible<Vec<(u32, PhoneNoInfo)>> {
+        let data = self.snapshot();
+        let pos = self.index_position(&data, no)?;
+
+        let start = pos.saturating_sub(window);
+        let end = pos.saturating_add(window).min(data.index.len() - 1);
+        (start..=end)
+            .map(|i| {
+                Ok((
+                    data.index[i].phone_no_prefix as u32,
+                    self.info_at(&data, i)?,
+                ))
+            })
+            .collect()
+    }
+
+    /// 查询号码所在号段，号段不存在时返回数值上最接近的已知号段
+    ///
+    /// 返回 `(号段, 归属地, 差值)`，差值为返回的号段减去号码前7位，精确命中时为 0；
+    /// 上下两侧距离相等时取较小的号段。用于数据补全时参考相邻号段，
+    /// 不经过结果缓存，也不计入查询统计。数据库为空时返回 [`ErrorKind::NotFound`]。
+    pub fn find_nearest(&self, no: &str) -> Fallible<(u32, PhoneNoInfo, i64)> {
+        self.check_length(no)?;
+        let no_parsed = self.parse_phone_prefix(no)?;
+        let data = self.snapshot();
+        let pos = match data
+            .index
+            .binary_search_by_key(&no_parsed, |idx| idx.phone_no_prefix)
+        {
+            Ok(pos) => pos,
+            Err(pos) => {
+                let distance = |i: usize| {
+                    (i64::from(data.index[i].phone_no_prefix) - i64::from(no_parsed)).abs()
+                };
+                match (pos.checked_sub(1), pos < data.index.len()) {
+                    (None, false) => return Err(ErrorKind::NotFound),
+                    (Some(below), false) => below,
+                    (None, true) => pos,
+                    (Some(below), true) if distance(below) <= distance(pos) => below,
+                    (Some(_), true) => pos,
+                }
+            }
+        };
+        let prefix = data.index[pos].phone_no_prefix;
+        Ok((
+            prefix as u32,
+            self.info_at(&data, pos)?,
+            i64::from(prefix) - i64::from(no_parsed),
+        ))
+    }
+
+    /// 按区号查询归属地，如 `"0755"`
+    ///
+    /// 同一区号对应多条记录时返回索引中最先出现的一条。返回结果的运营商为 `"固定电话"`。
+    pub fn find_by_area_code(&self, area_code: &str) -> Fallible<PhoneNoInfo> {
+        let data = self.snapshot();
+        let area_codes = data.area_codes.get_or_init(|| self.build_area_codes(&data));
+        let offset = *area_codes.get(area_code).ok_or(ErrorKind::NotFound)?;
+        let record = self.parse_to_record(&data, offset as usize)?;
+        Ok(PhoneNoInfo {
+            province: record.province,
+            city: record.city,
+            zip_code: record.zip_code,
+            area_code: record.area_code,
+            card_type: LANDLINE_CARD_TYPE.to_string(),
+            icon_key: LANDLINE_ICON_KEY.to_string(),
+            #[cfg(feature = "timezone")]
+            timezone: TIMEZONE.to_string(),
+        })
+    }
+
+    /// 解析带区号的固定电话号码（如 `075512345678`）并按区号查询
+    ///
+    /// 北京、上海、天津、重庆及 02x 地区使用 3 位区号，其余使用 4 位区号；
+    /// 区号之后须为 7 或 8 位本地号码。
+    fn find_landline(&self, no: &str) -> Fallible<PhoneNoInfo> {
+        let bytes = no.as_bytes();
+        if bytes.len() < 2 {
+            return Err(ErrorKind::InvalidLength);
+        }
+        if let Some(position) = bytes.iter().position(|b| !b.is_ascii_digit()) {
+            // 之前的字节都是 ASCII 数字，position 必然落在字符边界上
+            let found = no[position..].chars().next().unwrap_or('\u{fffd}');
+            return Err(ErrorKind::InvalidCharacter { position, found });
+        }
+        let area_code_len = landline_area_code_len(bytes);
+        let subscriber_len = bytes.len().saturating_sub(area_code_len);
+        if !(7..=8).contains(&subscriber_len) {
+            return Err(ErrorKind::InvalidLength);
+        }
+        self.find_by_area_code(&no[..area_code_len])
+    }
+
+    /// 构建区号到记录偏移的映射
+    fn build_area_codes(&self, data: &Snapshot) -> HashMap<String, i32> {
+        let mut area_codes = HashMap::new();
+        for index_item in data.index.iter() {
+            if let Ok(record) = self.parse_to_record(data, index_item.records_offset as usize) {
+                area_codes
+                    .entry(record.area_code)
+                    .or_insert(index_item.records_offset);
+            }
+        }
+        tracing::debug!("区号映射构建完成，区号数: {}", area_codes.len());
+        area_codes
+    }
+
+    /// 返回属于指定省份的全部7位号段前缀，按升序排列；省份不存在时返回空列表
+    ///
+    /// 首次调用时扫描全部索引构建 `省份 -> 前缀` 反向索引，之后直接查表。
+    /// 记录无法解析的号段不计入。
+    pub fn prefixes_by_province(&self, province: &str) -> Vec<u32> {
+        let data = self.snapshot();
+        data.province_prefixes
+            .get_or_init(|| self.build_province_prefixes(&data))
+            .get(province)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// 构建省份到号段前缀的反向索引，同一记录只解析一次
+    fn build_province_prefixes(&self, data: &Snapshot) -> HashMap<String, Vec<u32>> {
+        let mut provinces: HashMap<i32, Option<String>> = HashMap::new();
+        let mut prefixes: HashMap<String, Vec<u32>> = HashMap::new();
+        for index_item in data.index.iter() {
+            let province = provinces
+                .entry(index_item.records_offset)
+                .or_insert_with(|| {
+                    self.parse_to_record(data, index_item.records_offset as usize)
+                        .ok()
+                        .map(|record| record.province)
+                });
+            if let Some(province) = province {
+                prefixes
+                    .entry(province.clone())
+                    .or_default()
+                    .push(index_item.phone_no_prefix as u32);
+            }
+        }
+        tracing::debug!("省份前缀索引构建完成，省份数: {}", prefixes.len());
+        prefixes
+    }
+
+    /// 按城市名子串搜索号段，返回城市名包含 `keyword` 的全部 `(号段, 归属地)`，按号段升序排列
+    ///
+    /// 匹配记录中的原始城市名，区分大小写和全半角；`keyword` 为空时返回空列表。
+    /// 首次调用时扫描全部索引构建 `城市 -> 索引位置` 反向索引，之后只需遍历城市名。
+    /// 不经过结果缓存，也不计入查询统计；记录无法解析或运营商未知的号段会被跳过。
+    pub fn search_by_city(&self, keyword: &str) -> Vec<(u32, PhoneNoInfo)> {
+        if keyword.is_empty() {
+            return Vec::new();
+        }
+        let data = self.snapshot();
+        let mut positions: Vec<usize> = data
+            .city_positions
+            .get_or_init(|| self.build_city_positions(&data))
+            .iter()
+            .filter(|(city, _)| city.contains(keyword))
+            .flat_map(|(_, positions)| positions.iter().copied())
+            .collect();
+        positions.sort_unstable();
+        positions
+            .into_iter()
+            .filter_map(|pos| {
+                let info = self.info_at(&data, pos).ok()?;
+                Some((data.index[pos].phone_no_prefix as u32, info))
+            })
+            .collect()
+    }
+
+    /// 构建城市到索引位置的反向索引，同一记录只解析一次，城市为空的号段不计入
+    fn build_city_positions(&self, data: &Snapshot) -> HashMap<String, Vec<usize>> {
+        let mut cities: HashMap<i32, Option<String>> = HashMap::new();
+        let mut positions: HashMap<String, Vec<usize>> = HashMap::new();
+        for (pos, index_item) in data.index.iter().enumerate() {
+            let city = cities.entry(index_item.records_offset).or_insert_with(|| {
+                self.parse_to_record(data, index_item.records_offset as usize)
+                    .ok()
+                    .map(|record| record.city)
+                    .filter(|city| !city.is_empty())
+            });
+            if let Some(city) = city {
+                positions.entry(city.clone()).or_default().push(pos);
+            }
+        }
+        tracing::debug!("城市索引构建完成，城市数: {}", positions.len());
+        positions
+    }
+
+    /// 按索引位置分页读取 `[offset, offset + limit)` 范围内的号段，超出索引范围的部分被截断
+    ///
+    /// 索引顺序固定，适合管理界面按偏移稳定翻页。记录无法解析或运营商未知的号段会被跳过，
+    /// 因此一页的条目数可能少于 `limit`。不经过结果缓存，也不计入查询统计。
+    pub fn page(&self, offset: usize, limit: usize) -> Vec<(u32, PhoneNoInfo)> {
+        let data = self.snapshot();
+        let start = offset.min(data.index.len());
+        let end = offset.saturating_add(limit).min(data.index.len());
+        (start..end)
+            .filter_map(|pos| self.entry_at(&data, pos))
+            .collect()
+    }
+
+    /// 前缀落在闭区间 `[start_prefix, end_prefix]` 内的所有号段，按前缀升序返回
+    ///
+    /// 两次二分查找定位区间端点，端点不必是真实存在的前缀；`start_prefix > end_prefix` 时返回空。
+    /// 与 [`PhoneData::page`] 一样跳过无法解析的号段，不经过结果缓存，也不计入查询统计。
+    pub fn find_range(&self, start_prefix: u32, end_prefix: u32) -> Vec<(u32, PhoneNoInfo)> {
+        if start_prefix > end_prefix {
+            return Vec::new();
+        }
+        let data = self.snapshot();
+        let start = i64::from(start_prefix);
+        let end = i64::from(end_prefix);
+        let from = data
+            .index
+            .partition_point(|item| i64::from(item.phone_no_prefix) < start);
+        let to = data
+            .index
+            .partition_point(|item| i64::from(item.phone_no_prefix) <= end);
+        (from..to)
+            .filter_map(|pos| self.entry_at(&data, pos))
+            .collect()
+    }
+
+    /// 按索引顺序惰性遍历全部号段，每次 `next` 才解析对应记录
+    ///
+    /// 指向同一记录的多个号段各产出一项。与 [`PhoneData::page`] 一样，记录无法解析或运营商未知的号段会被跳过。
+    /// 不经过结果缓存，也不计入查询统计。遍历期间重新加载数据库不影响本次遍历。
+    pub fn iter(&self) -> impl Iterator<Item = (u32, PhoneNoInfo)> + '_ {
+        let data = self.snapshot();
+        (0..data.index.len()).filter_map(move |pos| self.entry_at(&data, pos))
+    }
+
+    /// 指定索引位置的号段及其归属地，无法解析时返回 `None`
+    fn entry_at(&self, data: &Snapshot, pos: usize) -> Option<(u32, PhoneNoInfo)> {
+        let info = self.info_at(data, pos).ok()?;
+        Some((data.index[pos].phone_no_prefix as u32, info))
+    }
+
+    /// 按配置的 [`CityFallback`] 补全空的城市字段
+    fn apply_city_fallback(&self, data: &Snapshot, pos: usize, record: &mut Records) {
+        match self.city_fallback {
+            CityFallback::None => {}
+            CityFallback::Province => record.city = record.province.clone(),
+            CityFallback::NearestPrefix => {
+                if let Some(city) = self
+                    .nearest_city_neighbor(data, pos, &record.province)
+                    .and_then(|i| {
+                        self.parse_to_record(data, data.index[i].records_offset as usize)
+                            .ok()
+                    })
+                    .map(|other| other.city)
+                {
+                    record.city = city;
+                }
+            }
+        }
+    }
+
+    /// 查找 `pos` 两侧同省内最近的、城市非空的索引位置
+    fn nearest_city_neighbor(&self, data: &Snapshot, pos: usize, province: &str) -> Option<usize> {
+        // 向两侧同时扩散，离开本省的一侧停止搜索
+        let mut left_open = true;
+        let mut right_open = true;
+        let mut distance = 1;
+        while left_open || right_open {
+            for (open, candidate) in [
+                (&mut left_open, pos.checked_sub(distance)),
+                (&mut right_open, pos.checked_add(distance)),
+            ] {
+                if !*open {
+                    continue;
+                }
+                let Some((i, neighbor)) = candidate.and_then(|i| Some((i, data.index.get(i)?)))
+                else {
+                    *open = false;
+                    continue;
+                };
+                match self.parse_to_record(data, neighbor.records_offset as usize) {
+                    Ok(other) if other.province != province => *open = false,
+                    Ok(other) if !other.city.is_empty() => return Some(i),
+                    Ok(_) => {}
+                    Err(_) => *open = false,
+                }
+            }
+            distance += 1;
+        }
+        None
+    }
+
+    /// 查询号码并与之前保存的结果快照比较，任一字段不同即视为已变化
+    ///
+    /// 适合在重新加载数据库后检测号码归属地是否发生变化。
+    pub fn has_changed(&self, no: &str, previous: &PhoneNoInfo) -> Fallible<bool> {
+        let current = self.find(no)?;
+        Ok(current != *previous)
+    }
+
+    /// 查询号码并返回共享字符串存储的结果
+    ///
+    /// 省、市、邮编、区号在所有记录间驻留为同一个 `Arc<str>`，克隆结果不产生堆分配，
+    /// 适合需要长期持有大量查询结果的场景。该方法不经过结果缓存，也不计入查询统计。
+    pub fn find_interned(&self, no: &str) -> Fallible<PhoneNoInfoInterned> {
+        let data = self.snapshot();
+        let pos = self.index_position(&data, no)?;
+        let index_item = &data.index[pos];
+
+        let interned = data.interned.get_or_init(|| self.build_interned(&data));
+        let mut info = interned
+            .get(&index_item.records_offset)
+            .cloned()
+            .ok_or(ErrorKind::InvalidPhoneDatabase)?;
+        info.card_type = CardType::from_u8(index_item.card_type)?.get_description();
+        Ok(info)
+    }
+
+    /// 只查询号码的运营商类型，不解析归属地记录
+    ///
+    /// 该方法不经过结果缓存，也不计入查询统计。
+    pub fn find_card_type(&self, no: &str) -> Fallible<CardType> {
+        let data = self.snapshot();
+        let pos = self.index_position(&data, no)?;
+        CardType::from_u8(data.index[pos].card_type)
+    }
+
+    /// 查询归属地，运营商以 [`CardType`] 枚举返回，便于调用方做逻辑判断
+    ///
+    /// 经过结果缓存并计入查询统计，与 [`PhoneData::find`] 行为一致。
+    /// 固定电话没有运营商，查询固定电话号码时返回 [`ErrorKind::InvalidOpNo`]。
+    pub fn find_typed(&self, no: &str) -> Fallible<PhoneNoInfoTyped> {
+        let info = self.find(no)?;
+        let carrier = CardType::from_icon_key(&info.icon_key).ok_or(ErrorKind::InvalidOpNo)?;
+        Ok(PhoneNoInfoTyped {
+            province: info.province,
+            city: info.city,
+            zip_code: info.zip_code,
+            area_code: info.area_code,
+            carrier,
+            is_virtual: carrier.is_virtual(),
+        })
+    }
+
+    /// 解析所有被索引引用的记录，并对重复字符串做驻留
+    fn build_interned(&self, data: &Snapshot) -> HashMap<i32, PhoneNoInfoInterned> {
+        let mut strings: HashMap<String, Arc<str>> = HashMap::new();
+        let mut intern = |value: String| -> Arc<str> {
+            strings
+                .entry(value)
+                .or_insert_with_key(|key| Arc::from(key.as_str()))
+                .clone()
+        };
+
+        let mut interned = HashMap::new();
+        for index_item in data.index.iter() {
+            if interned.contains_key(&index_item.records_offset) {
+                continue;
+            }
+            if let Ok(record) = self.parse_to_record(data, index_item.records_offset as usize) {
+                interned.insert(
+                    index_item.records_offset,
+                    PhoneNoInfoInterned {
+                        province: intern(record.province),
+                        city: intern(record.city),
+                        zip_code: intern(record.zip_code),
+                        area_code: intern(record.area_code),
+                        card_type: "",
+                    },
+                );
+            }
+        }
+        tracing::debug!("驻留记录构建完成，记录数: {}", interned.len());
+        interned
+    }
+
+    /// 批量查询并按地区聚合成功的结果
+    ///
+    /// 查询失败的号码原样放入 [`GroupedResults::errors`]。
+    pub fn find_grouped(&self, numbers: &[&str], by: GroupBy) -> GroupedResults {
+        let mut grouped = GroupedResults::default();
+        for &no in numbers {
+            match self.find(no) {
+                Ok(info) => grouped.groups.entry(by.key(&info)).or_default().push(info),
+                Err(_) => grouped.errors.push(no.to_string()),
+            }
+        }
+        grouped
+    }
+
+    /// 批量查询，结果顺序与输入一一对应
+    ///
+    /// 每个号码都按 [`PhoneData::find`] 的流程处理，共享结果缓存并计入查询统计；
+    /// 但同一批中前7位相同的号码只做一次二分查找，之后直接复用该号段的结果，
+    /// 复用的结果同样以各自的完整号码为键写入缓存。整批查询使用同一个数据快照。
+    pub fn find_batch(&self, numbers: &[&str]) -> Vec<Fallible<PhoneNoInfo>> {
+        let data = self.snapshot();
+        // 号段 -> 查询结果，`None` 表示号段不存在
+        let mut segments: HashMap<i32, Option<PhoneNoInfo>> = HashMap::new();
+        let mut search = |no: &str, prefix: i32| {
+            if let Some(found) = segments.get(&prefix) {
+                let info = found.clone().ok_or(ErrorKind::NotFound)?;
+                self.cache_result(&data, no, &info);
+                return Ok(info);
+            }
+            let result = self.search_prefix(&data, prefix);
+            if let Ok(info) = &result {
+                self.cache_result(&data, no, info);
+            }
+            match &result {
+                Ok(info) => {
+                    segments.insert(prefix, Some(info.clone()));
+                }
+                Err(ErrorKind::NotFound) => {
+                    segments.insert(prefix, None);
+                }
+                Err(_) => {}
+            }
+            result
+        };
+        numbers
+            .iter()
+            .map(|&no| self.find_with(no, &mut search))
+            .collect()
+    }
+
+    /// 批量查询并保留原始号码，结果顺序与输入一一对应
+    ///
+    /// 基于 [`PhoneData::find_batch`]，同一批中前7位相同的号码只做一次二分查找。
+    pub fn find_batch_map(&self, numbers: &[&str]) -> Vec<(String, Fallible<PhoneNoInfo>)> {
+        numbers
+            .iter()
+            .map(|&no| no.to_string())
+            .zip(self.find_batch(numbers))
+            .collect()
+    }
+
+    /// 批量查询并只保留属于指定运营商的结果，结果顺序与输入一一对应
+    ///
+    /// 运营商不匹配、查询失败或号码为固定电话时对应位置为 `None`。`include_virtual` 为 `true` 时，
+    /// 指定实网运营商（如 [`CardType::Cmcc`]）也会匹配其虚拟运营商（[`CardType::CmccV`]）；
+    /// 指定虚拟运营商时只匹配该虚拟运营商本身。
+    pub fn find_batch_by_carrier(
+        &self,
+        numbers: &[&str],
+        carrier: CardType,
+        include_virtual: bool,
+    ) -> Vec<Option<PhoneNoInfo>> {
+        numbers
+            .iter()
+            .map(|&no| {
+                let info = self.find(no).ok()?;
+                let actual = CardType::from_icon_key(&info.icon_key)?;
+                let matched = actual == carrier
+                    || (include_virtual && actual.is_virtual() && actual.network() == carrier);
+                matched.then_some(info)
+            })
+            .collect()
+    }
+
+    /// 批量查询并按指定依据排序
+    ///
+    /// 成功的结果按 `by` 稳定排序，依据相同时保持输入顺序；查询失败的号码按输入顺序排在最后。
+    pub fn find_batch_sorted(
+        &self,
+        numbers: &[&str],
+        by: SortKey,
+    ) -> Vec<(String, Result<PhoneNoInfo, ErrorKind>)> {
+        let mut results = self.find_batch_map(numbers);
+        results.sort_by(|(_, a), (_, b)| match (a, b) {
+            (Ok(a), Ok(b)) => by.compare(a, b),
+            (Ok(_), Err(_)) => std::cmp::Ordering::Less,
+            (Err(_), Ok(_)) => std::cmp::Ordering::Greater,
+            (Err(_), Err(_)) => std::cmp::Ordering::Equal,
+        });
+        results
+    }
+
+    /// 只校验号码格式和号段是否存在，不解析归属地
+    ///
+    /// 先检查字符再检查长度，因此 `"12a"` 返回 [`NumberValidity::NonDigit`]。
+    /// 号段只在手机号索引中查找，固定电话返回 [`NumberValidity::UnknownSegment`]。
+    /// 不经过结果缓存，也不计入查询统计，适合表单输入时的快速校验。
+    pub fn validate_number(&self, no: &str) -> NumberValidity {
+        if !no.bytes().all(|b| b.is_ascii_digit()) {
+            return NumberValidity::NonDigit;
+        }
+        if no.len() < 7 {
+            return NumberValidity::TooShort;
+        }
+        if self.strict_length && no.len() > 11 {
+            return NumberValidity::TooLong;
+        }
+        let Ok(prefix) = self.parse_phone_prefix(no) else {
+            return NumberValidity::NonDigit;
+        };
+        match self
+            .snapshot()
+            .index
+            .binary_search_by_key(&prefix, |idx| idx.phone_no_prefix)
+        {
+            Ok(_) => NumberValidity::Valid,
+            Err(_) => NumberValidity::UnknownSegment,
         }
     }
 
@@ -353,9 +2030,11 @@ impl PhoneData {
         }
 
         let mut result = 0i32;
-        for &digit in bytes.iter().take(7) {
+        for (position, &digit) in bytes.iter().take(7).enumerate() {
             if !digit.is_ascii_digit() {
-                return Err(ErrorKind::InvalidPhoneDatabase);
+                // 之前的字节都是 ASCII 数字，position 必然落在字符边界上
+                let found = no[position..].chars().next().unwrap_or('\u{fffd}');
+                return Err(ErrorKind::InvalidCharacter { position, found });
             }
             result = result * 10 + (digit - b'0') as i32;
         }
@@ -364,7 +2043,7 @@ impl PhoneData {
 
     /// 获取总记录数（用于Tauri命令）
     pub fn get_total_records(&self) -> usize {
-        self.index.len()
+        self.index_count()
     }
 
     /// 获取缓存统计信息
@@ -372,12 +2051,30 @@ impl PhoneData {
         let cache = self.cache.read().unwrap();
         CacheStats {
             size: cache.len(),
-            max_size: self.cache_max_size,
+            max_size: cache.capacity(),
             hits: self.cache_hits(),
+            misses: self.cache_misses(),
+            not_found: self.not_found_count(),
             total_queries: self.query_count(),
         }
     }
 
+    /// 按最近访问顺序淘汰缓存，直到条目数不超过 `target_entries`，返回淘汰的条目数
+    ///
+    /// 一次性收缩，不修改配置的最大条目数，适合由外部内存监控在内存紧张时调用。
+    pub fn trim_cache_to(&self, target_entries: usize) -> usize {
+        let evicted = self.cache.write().unwrap().trim_to(target_entries);
+        if evicted > 0 {
+            tracing::info!("缓存收缩完成，淘汰 {} 个条目", evicted);
+        }
+        evicted
+    }
+
+    /// 估算结果缓存当前占用的内存字节数
+    pub fn cache_memory_bytes(&self) -> usize {
+        self.cache.read().unwrap().estimated_bytes()
+    }
+
     /// 清空缓存
     pub fn clear_cache(&self) -> Result<(), ErrorKind> {
         if !self.cache_enabled {
@@ -392,26 +2089,55 @@ impl PhoneData {
         Ok(())
     }
 
+    /// 缓存最大条目数
+    pub fn cache_max_size(&self) -> usize {
+        self.cache.read().unwrap().capacity()
+    }
+
     /// 设置缓存大小
-    pub fn set_cache_size(&self, _new_size: usize) -> Result<(), ErrorKind> {
+    ///
+    /// 新上限小于当前条目数时按 LRU 顺序淘汰多余条目，其余条目保留。
+    /// `new_size` 为 0 时返回 [`ErrorKind::InvalidCacheSize`]。
+    pub fn set_cache_size(&self, new_size: usize) -> Result<(), ErrorKind> {
         if !self.cache_enabled {
             return Err(ErrorKind::InvalidPhoneDatabase);
         }
+        if new_size == 0 {
+            return Err(ErrorKind::InvalidCacheSize);
+        }
 
-        // 注意：这里只是展示接口，实际实现可能需要重构缓存结构
-        // 当前实现只是清空缓存
         let mut cache = self
             .cache
             .write()
             .map_err(|_| ErrorKind::InvalidPhoneDatabase)?;
-        cache.clear();
-        // TODO: 实际应用中可能需要调整PhoneData结构来支持动态缓存大小调整
+        let evicted = cache.set_capacity(new_size);
+        tracing::info!("缓存上限已调整为 {}，淘汰 {} 个条目", new_size, evicted);
         Ok(())
     }
 }
 
+/// 按 `YYMM`、`YYYYMM` 或 `YYYYMMDD` 解析数据库版本
+fn parse_version_date(version: &str) -> Option<chrono::NaiveDate> {
+    let version = version.trim();
+    if !version.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let number = |range: std::ops::Range<usize>| version[range].parse::<u32>().ok();
+    let (year, month, day) = match version.len() {
+        4 => (2000 + number(0..2)?, number(2..4)?, 1),
+        6 => (number(0..4)?, number(4..6)?, 1),
+        8 => (number(0..4)?, number(4..6)?, number(6..8)?),
+        _ => return None,
+    };
+    chrono::NaiveDate::from_ymd_opt(year as i32, month, day)
+}
+
+/// `phone.dat` 记录字段的默认分隔符
+const DEFAULT_RECORD_DELIMITER: char = '|';
+
 /// 缓存统计信息结构
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
 pub struct CacheStats {
     /// 当前缓存条目数
     pub size: usize,
@@ -419,13 +2145,77 @@ pub struct CacheStats {
     pub max_size: usize,
     /// 缓存命中次数
     pub hits: u64,
+    /// 缓存未命中次数
+    pub misses: u64,
+    /// 数据库中未找到的查询次数
+    pub not_found: u64,
     /// 总查询次数
     pub total_queries: u64,
 }
 
-/// 运营商类型，使用更紧凑的表示
+/// 评估缓存健康状况所需的最少查询数
+pub const CACHE_HEALTH_MIN_QUERIES: u64 = 1000;
+
+/// 缓存被视为有效的最低命中率（百分比）
+pub const CACHE_HEALTH_MIN_HIT_RATE: f64 = 5.0;
+
+/// 缓存健康状况
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum CardType {
+pub enum CacheHealth {
+    /// 缓存未启用
+    Disabled,
+    /// 查询数不足，暂无法判断
+    Warming,
+    /// 命中率正常
+    Healthy,
+    /// 命中率过低，缓存基本没有发挥作用
+    Ineffective,
+}
+
+/// 结果缓存的淘汰策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EvictionPolicy {
+    /// 淘汰最久未访问的条目（默认）
+    #[default]
+    Lru,
+    /// 淘汰命中次数最少的条目，适合少量热点号码被反复查询的负载
+    Lfu,
+    /// 淘汰最早插入的条目，适合批量遍历型负载，命中时不调整顺序
+    Fifo,
+}
+
+impl std::str::FromStr for EvictionPolicy {
+    type Err = String;
+
+    /// 解析配置中的策略名 `lru`、`lfu` 或 `fifo`，不区分大小写
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "lru" => Ok(EvictionPolicy::Lru),
+            "lfu" => Ok(EvictionPolicy::Lfu),
+            "fifo" => Ok(EvictionPolicy::Fifo),
+            _ => Err(format!("未知的缓存淘汰策略: {}", s)),
+        }
+    }
+}
+
+/// gzip 文件头的魔数
+#[cfg(feature = "gzip")]
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// 固定电话查询结果的运营商字段
+pub const LANDLINE_CARD_TYPE: &str = "固定电话";
+
+/// 固定电话查询结果的英文运营商字段
+pub const LANDLINE_CARD_TYPE_EN: &str = "Landline";
+
+/// 固定电话查询结果的图标标识
+pub const LANDLINE_ICON_KEY: &str = "landline";
+
+/// 运营商类型，使用更紧凑的表示
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CardType {
+    /// 部分第三方数据库用 0 表示运营商未知，省市等地理信息仍然可用
+    Unknown = 0,
     Cmcc = 1,
     Cucc = 2,
     Ctcc = 3,
@@ -440,6 +2230,7 @@ impl CardType {
     #[inline]
     fn from_u8(i: u8) -> Result<CardType, ErrorKind> {
         match i {
+            0 => Ok(CardType::Unknown),
             1 => Ok(CardType::Cmcc),
             2 => Ok(CardType::Cucc),
             3 => Ok(CardType::Ctcc),
@@ -456,6 +2247,7 @@ impl CardType {
     #[inline]
     const fn get_description(&self) -> &'static str {
         match self {
+            CardType::Unknown => "未知运营商",
             CardType::Cmcc => "中国移动",
             CardType::Cucc => "中国联通",
             CardType::Ctcc => "中国电信",
@@ -466,54 +2258,590 @@ impl CardType {
             CardType::CbccV => "中国广电虚拟运营商",
         }
     }
-}
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct PhoneNoInfo {
-    /// 省
-    pub province: String,
-    /// 市
-    pub city: String,
-    /// 邮政编码
-    pub zip_code: String,
-    /// 长途区号
-    pub area_code: String,
-    /// 卡类型
+    /// 运营商图标标识，供前端映射到自己的图标资源
+    ///
+    /// 返回值是稳定的 ASCII 标识，不随描述文案变化：
+    ///
+    /// | 运营商 | 标识 |
+    /// |--------|------|
+    /// | 中国移动 | `cmcc` |
+    /// | 中国联通 | `cucc` |
+    /// | 中国电信 | `ctcc` |
+    /// | 中国广电 | `cbcc` |
+    /// | 虚拟运营商 | 对应运营商标识加 `_v` 后缀，如 `cmcc_v` |
+    /// | 未知运营商 | `unknown` |
+    #[inline]
+    pub const fn icon_key(&self) -> &'static str {
+        match self {
+            CardType::Unknown => "unknown",
+            CardType::Cmcc => "cmcc",
+            CardType::Cucc => "cucc",
+            CardType::Ctcc => "ctcc",
+            CardType::CtccV => "ctcc_v",
+            CardType::CuccV => "cucc_v",
+            CardType::CmccV => "cmcc_v",
+            CardType::Cbcc => "cbcc",
+            CardType::CbccV => "cbcc_v",
+        }
+    }
+
+    /// 运营商英文名称
+    #[inline]
+    pub const fn as_str_en(&self) -> &'static str {
+        match self {
+            CardType::Unknown => "Unknown Carrier",
+            CardType::Cmcc => "China Mobile",
+            CardType::Cucc => "China Unicom",
+            CardType::Ctcc => "China Telecom",
+            CardType::CtccV => "China Telecom MVNO",
+            CardType::CuccV => "China Unicom MVNO",
+            CardType::CmccV => "China Mobile MVNO",
+            CardType::Cbcc => "China Broadnet",
+            CardType::CbccV => "China Broadnet MVNO",
+        }
+    }
+
+    /// 是否为虚拟运营商
+    #[inline]
+    pub const fn is_virtual(&self) -> bool {
+        matches!(
+            self,
+            CardType::CtccV | CardType::CuccV | CardType::CmccV | CardType::CbccV
+        )
+    }
+
+    /// 基础运营商的英文代码：`CMCC`、`CUCC`、`CTCC` 或 `CBCC`，未知运营商为 `UNKNOWN`
+    ///
+    /// 虚拟运营商返回承载它的实网运营商代码，需要区分时配合 [`CardType::is_virtual`] 使用。
+    #[inline]
+    pub const fn carrier_code(&self) -> &'static str {
+        match self.network() {
+            CardType::Unknown => "UNKNOWN",
+            CardType::Cmcc => "CMCC",
+            CardType::Cucc => "CUCC",
+            CardType::Ctcc => "CTCC",
+            _ => "CBCC",
+        }
+    }
+
+    /// 所属的基础运营商：虚拟运营商返回承载它的实网运营商，实网运营商和未知运营商返回自身
+    #[inline]
+    pub const fn network(&self) -> CardType {
+        match self {
+            CardType::Unknown => CardType::Unknown,
+            CardType::Cmcc | CardType::CmccV => CardType::Cmcc,
+            CardType::Cucc | CardType::CuccV => CardType::Cucc,
+            CardType::Ctcc | CardType::CtccV => CardType::Ctcc,
+            CardType::Cbcc | CardType::CbccV => CardType::Cbcc,
+        }
+    }
+
+    /// 由 [`CardType::icon_key`] 反查运营商
+    fn from_icon_key(key: &str) -> Option<CardType> {
+        [
+            CardType::Unknown,
+            CardType::Cmcc,
+            CardType::Cucc,
+            CardType::Ctcc,
+            CardType::CtccV,
+            CardType::CuccV,
+            CardType::CmccV,
+            CardType::Cbcc,
+            CardType::CbccV,
+        ]
+        .into_iter()
+        .find(|card_type| card_type.icon_key() == key)
+    }
+}
+
+impl std::fmt::Display for CardType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.get_description())
+    }
+}
+
+/// [`PhoneData::find_ref`] 的查询结果，字段直接借用数据库记录区
+///
+/// 结果持有查询时的数据库快照，[`PhoneData::reload`] 之后仍读取旧数据。
+/// 需要保存或序列化时用 [`PhoneNoInfoRef::to_info`] 转为 [`PhoneNoInfo`]。
+#[derive(Clone)]
+pub struct PhoneNoInfoRef<'a> {
+    data: Arc<Snapshot>,
+    province: Range<usize>,
+    city: Range<usize>,
+    zip_code: Range<usize>,
+    area_code: Range<usize>,
+    card_type: &'a str,
+    carrier: CardType,
+}
+
+/// 记录区中 `range` 处的字段，范围由 [`PhoneData::record_ranges`] 按 UTF-8 字符边界生成
+fn record_field<'d>(data: &'d Snapshot, range: &Range<usize>) -> &'d str {
+    std::str::from_utf8(&data.records[range.clone()]).unwrap_or_default()
+}
+
+impl PhoneNoInfoRef<'_> {
+    /// 省
+    pub fn province(&self) -> &str {
+        record_field(&self.data, &self.province)
+    }
+
+    /// 市
+    pub fn city(&self) -> &str {
+        record_field(&self.data, &self.city)
+    }
+
+    /// 邮政编码
+    pub fn zip_code(&self) -> &str {
+        record_field(&self.data, &self.zip_code)
+    }
+
+    /// 长途区号
+    pub fn area_code(&self) -> &str {
+        record_field(&self.data, &self.area_code)
+    }
+
+    /// 卡类型，与 [`PhoneNoInfo::card_type`] 相同，包含自定义运营商名称
+    pub fn card_type(&self) -> &str {
+        self.card_type
+    }
+
+    /// 运营商
+    pub fn carrier(&self) -> CardType {
+        self.carrier
+    }
+
+    /// 运营商图标标识，见 [`CardType::icon_key`]
+    pub fn icon_key(&self) -> &'static str {
+        self.carrier.icon_key()
+    }
+
+    /// 转换为 owned 的查询结果，与 `find` 的返回值相同
+    pub fn to_info(&self) -> PhoneNoInfo {
+        PhoneNoInfo {
+            province: self.province().to_string(),
+            city: self.city().to_string(),
+            zip_code: self.zip_code().to_string(),
+            area_code: self.area_code().to_string(),
+            card_type: self.card_type.to_string(),
+            icon_key: self.icon_key().to_string(),
+            #[cfg(feature = "timezone")]
+            timezone: TIMEZONE.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Debug for PhoneNoInfoRef<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PhoneNoInfoRef")
+            .field("province", &self.province())
+            .field("city", &self.city())
+            .field("zip_code", &self.zip_code())
+            .field("area_code", &self.area_code())
+            .field("card_type", &self.card_type)
+            .finish()
+    }
+}
+
+/// [`PhoneData::find_typed`] 的查询结果，运营商为结构化的 [`CardType`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhoneNoInfoTyped {
+    /// 省
+    pub province: String,
+    /// 市
+    pub city: String,
+    /// 邮政编码
+    pub zip_code: String,
+    /// 长途区号
+    pub area_code: String,
+    /// 运营商
+    pub carrier: CardType,
+    /// 是否为虚拟运营商
+    pub is_virtual: bool,
+}
+
+/// 查询结果描述文字的语言
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    /// 中文（默认），使用数据库原文和内置或自定义的运营商名称
+    #[default]
+    Zh,
+    /// 英文，运营商使用英文名称；数据库没有拼音地名，省市仍为中文
+    En,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PhoneNoInfo {
+    /// 省
+    pub province: String,
+    /// 市
+    pub city: String,
+    /// 邮政编码
+    pub zip_code: String,
+    /// 长途区号
+    pub area_code: String,
+    /// 卡类型
     pub card_type: String,
+    /// 运营商图标标识，见 [`CardType::icon_key`]
+    #[serde(default)]
+    pub icon_key: String,
+    /// IANA 时区名，本数据库均为中国号码，恒为 [`TIMEZONE`]
+    #[cfg(feature = "timezone")]
+    #[serde(default)]
+    pub timezone: String,
+}
+
+/// 查询结果中的时区，中国全境统一使用该时区
+#[cfg(feature = "timezone")]
+pub const TIMEZONE: &str = "Asia/Shanghai";
+
+/// 直辖市，数据库中以不带"市"的简称表示
+pub const MUNICIPALITIES: [&str; 4] = ["北京", "上海", "天津", "重庆"];
+
+/// 自治区，数据库中以不带"自治区"的简称表示
+pub const AUTONOMOUS_REGIONS: [&str; 5] = ["内蒙古", "广西", "西藏", "宁夏", "新疆"];
+
+impl PhoneNoInfo {
+    /// 是否属于直辖市（北京、上海、天津、重庆）
+    pub fn is_municipality(&self) -> bool {
+        MUNICIPALITIES.contains(&self.province.as_str())
+    }
+
+    /// 是否属于自治区（内蒙古、广西、西藏、宁夏、新疆）
+    pub fn is_autonomous_region(&self) -> bool {
+        AUTONOMOUS_REGIONS.contains(&self.province.as_str())
+    }
+
+    /// 结构化的运营商类型，由 `icon_key` 反查，不受自定义运营商名称影响；固定电话返回 `None`
+    pub fn carrier(&self) -> Option<CardType> {
+        CardType::from_icon_key(&self.icon_key)
+    }
+}
+
+/// 使用驻留字符串的查询结果，相同的省市等字段共享同一份存储
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhoneNoInfoInterned {
+    /// 省
+    pub province: Arc<str>,
+    /// 市
+    pub city: Arc<str>,
+    /// 邮政编码
+    pub zip_code: Arc<str>,
+    /// 长途区号
+    pub area_code: Arc<str>,
+    /// 卡类型
+    pub card_type: &'static str,
+}
+
+/// 批量查询结果的分组维度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    /// 按省份分组，键如 `广东`
+    Province,
+    /// 按城市分组，键如 `广东/深圳`
+    City,
+}
+
+impl GroupBy {
+    /// 计算查询结果所属分组的键
+    pub fn key(&self, info: &PhoneNoInfo) -> String {
+        match self {
+            GroupBy::Province => info.province.clone(),
+            GroupBy::City => format!("{}/{}", info.province, info.city),
+        }
+    }
+}
+
+/// 批量查询结果的排序依据
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortKey {
+    /// 按省份名称排序
+    Province,
+    /// 按省份、城市名称排序
+    City,
+    /// 按运营商名称排序
+    Carrier,
+}
+
+impl SortKey {
+    /// 按排序依据比较两个查询结果
+    fn compare(&self, a: &PhoneNoInfo, b: &PhoneNoInfo) -> std::cmp::Ordering {
+        match self {
+            SortKey::Province => a.province.cmp(&b.province),
+            SortKey::City => (&a.province, &a.city).cmp(&(&b.province, &b.city)),
+            SortKey::Carrier => a.card_type.cmp(&b.card_type),
+        }
+    }
+}
+
+/// 按地区聚合的批量查询结果
+#[derive(Debug, Clone, Default)]
+pub struct GroupedResults {
+    /// 分组键到该地区查询结果的映射
+    pub groups: HashMap<String, Vec<PhoneNoInfo>>,
+    /// 查询失败的号码
+    pub errors: Vec<String>,
+}
+
+/// 判断输入是否可能是中国大陆手机号
+///
+/// 要求长度为7-11位、全部为数字、以 `1` 开头且第二位为 `3`-`9`。
+/// 只做形式检查，不代表号段一定存在于数据库中。
+pub fn is_plausible_mobile(no: &str) -> bool {
+    let bytes = no.as_bytes();
+    (7..=11).contains(&bytes.len()) && has_mobile_shape(bytes)
+}
+
+/// 不限长度的手机号形式检查：全部为数字、以 `1` 开头且第二位为 `3`-`9`
+fn has_mobile_shape(bytes: &[u8]) -> bool {
+    bytes.len() >= 2
+        && bytes.iter().all(u8::is_ascii_digit)
+        && bytes[0] == b'1'
+        && (b'3'..=b'9').contains(&bytes[1])
+}
+
+/// 号码脱敏，把第4-7位替换为星号，如 `138****8000`
+///
+/// 不足3位的输入原样返回。
+pub fn mask_number(no: &str) -> String {
+    no.chars()
+        .enumerate()
+        .map(|(i, c)| if (3..7).contains(&i) { '*' } else { c })
+        .collect()
+}
+
+/// 规范化用户输入的号码，返回纯数字串
+///
+/// 去除空格、连字符和括号，以及前导国家码 `+86`、`0086` 或 `86`（仅当去掉后剩余的仍像手机号时）。
+/// 含有其他字符或规范化后为空时返回 `None`；不检查长度。
+pub fn normalize_number(input: &str) -> Option<String> {
+    let mut digits = String::with_capacity(input.len());
+    let mut explicit_country_code = false;
+    for c in input.chars() {
+        match c {
+            '0'..='9' => digits.push(c),
+            ' ' | '-' | '(' | ')' => {}
+            // 加号只能出现在任何数字之前，且只能出现一次
+            '+' if digits.is_empty() && !explicit_country_code => explicit_country_code = true,
+            _ => return None,
+        }
+    }
+    let rest = if explicit_country_code {
+        digits.strip_prefix("86")?
+    } else if let Some(rest) = digits.strip_prefix("0086") {
+        rest
+    } else {
+        match digits.strip_prefix("86") {
+            Some(rest) if rest.len() == 11 && rest.starts_with('1') => rest,
+            _ => &digits,
+        }
+    };
+    if rest.is_empty() {
+        return None;
+    }
+    Some(rest.to_string())
+}
+
+/// [`normalize_number`] 拒绝输入的原因：第一个无法识别的字符，
+/// 或开头的加号后不是国家码 86；都没有时说明去掉国家码后没有剩余数字
+fn normalize_error(input: &str) -> ErrorKind {
+    let mut digits = String::new();
+    let mut plus = None;
+    for (position, found) in input.char_indices() {
+        match found {
+            '0'..='9' => digits.push(found),
+            ' ' | '-' | '(' | ')' => {}
+            '+' if digits.is_empty() && plus.is_none() => plus = Some(position),
+            _ => return ErrorKind::InvalidCharacter { position, found },
+        }
+    }
+    match plus {
+        Some(position) if !digits.starts_with("86") => ErrorKind::InvalidCharacter {
+            position,
+            found: '+',
+        },
+        _ => ErrorKind::InvalidLength,
+    }
+}
+
+/// 空字段视为未找到
+fn non_empty(field: String) -> Fallible<String> {
+    if field.is_empty() {
+        Err(ErrorKind::NotFound)
+    } else {
+        Ok(field)
+    }
+}
+
+/// 固定电话号码的区号长度：`01x`/`02x` 为 3 位，其余为 4 位，调用方须保证至少 2 位
+fn landline_area_code_len(bytes: &[u8]) -> usize {
+    if matches!(bytes[1], b'1' | b'2') {
+        3
+    } else {
+        4
+    }
+}
+
+/// 号码类型的粗略判断结果，见 [`classify_number`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberKind {
+    /// 手机号，应使用 [`PhoneData::find`]
+    Mobile,
+    /// 带区号的固定电话，应使用 [`PhoneData::find_by_area_code`]
+    Landline,
+    /// 无法判断
+    Unknown,
+}
+
+/// 号码格式预检结果，见 [`PhoneData::validate_number`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberValidity {
+    /// 少于7位
+    TooShort,
+    /// 多于11位（仅在 `strict_length` 开启时）
+    TooLong,
+    /// 含有非数字字符
+    NonDigit,
+    /// 格式正确，但前7位不是数据库中的号段
+    UnknownSegment,
+    /// 格式正确且号段存在
+    Valid,
+}
+
+/// 在查询前根据长度和首位数字判断输入更像手机号还是固定电话
+///
+/// 以 `1` 开头的 11 位数字视为手机号；以 `0` 开头、区号之后为 7-8 位本地号码的视为固定电话。
+/// 只做形式判断，不查询数据库。
+pub fn classify_number(input: &str) -> NumberKind {
+    let bytes = input.as_bytes();
+    if bytes.len() < 2 || !bytes.iter().all(u8::is_ascii_digit) {
+        return NumberKind::Unknown;
+    }
+    match bytes[0] {
+        b'1' if bytes.len() == 11 => NumberKind::Mobile,
+        b'0' if (7..=8).contains(&(bytes.len().saturating_sub(landline_area_code_len(bytes)))) => {
+            NumberKind::Landline
+        }
+        _ => NumberKind::Unknown,
+    }
 }
 
 /// 获取资源文件路径
 ///
-/// 在 Tauri 应用中，资源文件的位置在开发和生产环境中可能不同。
-/// 此函数尝试多种路径来定位资源文件。
-fn get_resource_path(filename: &str) -> String {
-    // 尝试多种可能的路径
-    let possible_paths = vec![
-        // 1. 当前工作目录（开发环境）
-        format!("./{}", filename),
-        // 2. 项目根目录（相对于 src-tauri）
-        format!("../{}", filename),
-        // 3. 绝对路径（如果环境变量设置）
-        std::env::var("PHONE_DATA_PATH").unwrap_or_default(),
+/// 资源文件的位置随部署方式不同（开发环境、Tauri 应用、系统服务等），依次查找：
+/// 1. 环境变量 `PHONE_DATA_PATH` 指向的文件
+/// 2. 当前工作目录
+/// 3. 上级目录（Tauri 开发环境下工作目录为 `src-tauri`）
+/// 4. 可执行文件所在目录
+/// 5. 平台数据目录下的 `phone-lookup-rs` 子目录（如 Linux 上的 `$XDG_DATA_HOME/phone-lookup-rs`）
+///
+/// 全部找不到时返回 [`std::io::ErrorKind::NotFound`]，错误信息中列出所有尝试过的路径。
+fn get_resource_path(filename: &str) -> Fallible<String> {
+    let mut search_dirs = vec![
+        std::path::PathBuf::from("."),
+        std::path::PathBuf::from(".."),
     ];
+    if let Some(dir) = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(std::path::Path::to_path_buf))
+    {
+        search_dirs.push(dir);
+    }
+    if let Some(dir) = dirs::data_dir() {
+        search_dirs.push(dir.join(DATA_DIR_NAME));
+    }
+
+    let env_value = std::env::var(DATA_PATH_ENV).ok();
+    resolve_resource_path(filename, env_value.as_deref(), &search_dirs).map_err(|tried| {
+        tracing::error!("未找到资源文件: {}，已尝试: {:?}", filename, tried);
+        ErrorKind::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("未找到资源文件 {}，已尝试: {}", filename, tried.join(", ")),
+        ))
+    })
+}
+
+/// 按 [`get_resource_path`] 的顺序查找资源文件，找不到时返回尝试过的全部路径
+fn resolve_resource_path(
+    filename: &str,
+    env_value: Option<&str>,
+    search_dirs: &[std::path::PathBuf],
+) -> Result<String, Vec<String>> {
+    let mut tried = Vec::new();
+    match check_env_data_path(env_value) {
+        EnvDataPath::Found(path) => {
+            tracing::info!("找到资源文件: {}", path);
+            return Ok(path);
+        }
+        EnvDataPath::Unset => {}
+        EnvDataPath::Blank => {
+            tracing::warn!("环境变量 {} 已设置但为空，已忽略", DATA_PATH_ENV);
+        }
+        EnvDataPath::NotAFile(path) => {
+            tracing::warn!(
+                "环境变量 {} 已设置，但指向的文件不存在或不是普通文件: {}",
+                DATA_PATH_ENV,
+                path
+            );
+            tried.push(path);
+        }
+    }
 
-    for path in possible_paths {
-        if !path.is_empty() && std::path::Path::new(&path).exists() {
+    for dir in search_dirs {
+        let path = dir.join(filename);
+        if path.is_file() {
+            let path = path.to_string_lossy().into_owned();
             tracing::info!("找到资源文件: {}", path);
-            return path;
+            return Ok(path);
         }
+        tried.push(path.to_string_lossy().into_owned());
     }
+    Err(tried)
+}
+
+/// 平台数据目录下存放数据文件的子目录名
+const DATA_DIR_NAME: &str = "phone-lookup-rs";
 
-    // 如果都找不到，返回默认路径（用于错误信息）
-    tracing::warn!("未找到资源文件: {}，将使用默认路径", filename);
-    format!("./{}", filename)
+/// 指定数据文件路径的环境变量
+const DATA_PATH_ENV: &str = "PHONE_DATA_PATH";
+
+/// 数据文件路径环境变量的检查结果
+#[derive(Debug, PartialEq, Eq)]
+enum EnvDataPath {
+    /// 未设置
+    Unset,
+    /// 已设置，但去掉空白后为空
+    Blank,
+    /// 已设置，但路径不存在或不是普通文件（如 `.`）
+    NotAFile(String),
+    /// 指向存在的文件
+    Found(String),
+}
+
+/// 去掉首尾空白后检查环境变量给出的数据文件路径
+fn check_env_data_path(value: Option<&str>) -> EnvDataPath {
+    let Some(value) = value else {
+        return EnvDataPath::Unset;
+    };
+    let path = value.trim();
+    if path.is_empty() {
+        EnvDataPath::Blank
+    } else if std::path::Path::new(path).is_file() {
+        EnvDataPath::Found(path.to_string())
+    } else {
+        EnvDataPath::NotAFile(path.to_string())
+    }
 }
 
 /// 统一的结果类型别名
 type Fallible<T> = Result<T, ErrorKind>;
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
 
     #[test]
@@ -549,7 +2877,21 @@ mod tests {
     fn test_parse_phone_prefix_invalid_chars() {
         let phone_data = create_mock_phone_data();
         let result = phone_data.parse_phone_prefix("138abc7");
-        assert!(matches!(result, Err(ErrorKind::InvalidPhoneDatabase)));
+        assert!(matches!(
+            result,
+            Err(ErrorKind::InvalidCharacter {
+                position: 3,
+                found: 'a'
+            })
+        ));
+        let result = phone_data.parse_phone_prefix("13８0013");
+        assert!(matches!(
+            result,
+            Err(ErrorKind::InvalidCharacter {
+                position: 2,
+                found: '８'
+            })
+        ));
     }
 
     #[test]
@@ -557,7 +2899,9 @@ mod tests {
         assert_eq!(CardType::from_u8(1).unwrap(), CardType::Cmcc);
         assert_eq!(CardType::from_u8(2).unwrap(), CardType::Cucc);
         assert_eq!(CardType::from_u8(3).unwrap(), CardType::Ctcc);
+        assert_eq!(CardType::from_u8(0).unwrap(), CardType::Unknown);
 
+        assert!(matches!(CardType::from_u8(9), Err(ErrorKind::InvalidOpNo)));
         assert!(matches!(CardType::from_u8(99), Err(ErrorKind::InvalidOpNo)));
     }
 
@@ -568,6 +2912,34 @@ mod tests {
         assert_eq!(CardType::Ctcc.get_description(), "中国电信");
     }
 
+    #[test]
+    fn test_card_type_icon_key() {
+        let expected = [
+            (CardType::Cmcc, "cmcc"),
+            (CardType::Cucc, "cucc"),
+            (CardType::Ctcc, "ctcc"),
+            (CardType::CtccV, "ctcc_v"),
+            (CardType::CuccV, "cucc_v"),
+            (CardType::CmccV, "cmcc_v"),
+            (CardType::Cbcc, "cbcc"),
+            (CardType::CbccV, "cbcc_v"),
+            (CardType::Unknown, "unknown"),
+        ];
+        for (card_type, key) in expected {
+            assert_eq!(card_type.icon_key(), key);
+        }
+    }
+
+    #[test]
+    fn test_card_type_carrier_code() {
+        assert_eq!(CardType::Cmcc.carrier_code(), "CMCC");
+        assert_eq!(CardType::CmccV.carrier_code(), "CMCC");
+        assert_eq!(CardType::CuccV.carrier_code(), "CUCC");
+        assert_eq!(CardType::Ctcc.carrier_code(), "CTCC");
+        assert_eq!(CardType::CbccV.carrier_code(), "CBCC");
+        assert_eq!(CardType::Unknown.carrier_code(), "UNKNOWN");
+    }
+
     #[test]
     fn test_index_ordering() {
         let index1 = Index {
@@ -586,17 +2958,2040 @@ mod tests {
     }
 
     /// 创建一个模拟的PhoneData实例用于测试
-    fn create_mock_phone_data() -> PhoneData {
+    pub(crate) fn create_mock_phone_data() -> PhoneData {
         PhoneData {
-            version: "TEST".to_string(),
-            records: Arc::new(vec![]),
-            index: Arc::new(vec![]),
-            cache: Arc::new(RwLock::new(HashMap::new())),
+            data: Snapshot::new("TEST".to_string(), RecordBytes::Owned(vec![]), vec![])
+                .into_shared(),
+            cache: Arc::new(RwLock::new(cache::ResultCache::new(100))),
             cache_enabled: true,
-            cache_max_size: 100,
-            query_count: AtomicU64::new(0),
-            cache_hits: AtomicU64::new(0),
+            stats: Arc::new(QueryStats::default()),
+            city_fallback: CityFallback::None,
+            reject_implausible: false,
+            strict_length: true,
+            slow_query_threshold: None,
+            record_delimiter: DEFAULT_RECORD_DELIMITER,
+            carrier_labels: Arc::new(HashMap::new()),
+            default_region: None,
+            geo: Arc::new(HashMap::new()),
+            query_observer: RwLock::new(None),
+        }
+    }
+
+    /// 根据 `(前缀, "省|市|邮编|区号", 卡类型)` 列表构造内存数据库，相同记录共享偏移
+    pub(crate) fn create_mock_phone_data_with(entries: &[(i32, &str, u8)]) -> PhoneData {
+        let mut records = Vec::new();
+        let mut offsets: HashMap<&str, i32> = HashMap::new();
+        let mut index = Vec::new();
+        for &(phone_no_prefix, record, card_type) in entries {
+            let records_offset = *offsets.entry(record).or_insert_with(|| {
+                let offset = records.len() as i32 + 8;
+                records.extend_from_slice(record.as_bytes());
+                records.push(0);
+                offset
+            });
+            index.push(Index {
+                phone_no_prefix,
+                records_offset,
+                card_type,
+            });
+        }
+        index.sort();
+
+        PhoneData {
+            data: Snapshot::new("TEST".to_string(), RecordBytes::Owned(records), index)
+                .into_shared(),
+            ..create_mock_phone_data()
+        }
+    }
+
+    /// 修改测试实例的索引，记录区保持不变
+    pub(crate) fn replace_index(phone_data: &PhoneData, f: impl FnOnce(&mut Vec<Index>)) {
+        let current = phone_data.snapshot();
+        let mut index = current.index.clone();
+        f(&mut index);
+        let records = RecordBytes::Owned(current.records.to_vec());
+        *phone_data.data.write().unwrap() =
+            Arc::new(Snapshot::new(current.version.clone(), records, index));
+    }
+
+    #[test]
+    fn test_has_changed_detects_city_change() {
+        let base = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380014, "北京|北京|100000|010", 1),
+        ]);
+        let patched = create_mock_phone_data_with(&[
+            (1380013, "河北|石家庄|050000|0311", 1),
+            (1380014, "北京|北京|100000|010", 1),
+        ]);
+
+        let snapshot = base.find("13800138000").unwrap();
+        assert!(!base.has_changed("13800138000", &snapshot).unwrap());
+        assert!(patched.has_changed("13800138000", &snapshot).unwrap());
+
+        let untouched = base.find("13800148000").unwrap();
+        assert!(!patched.has_changed("13800148000", &untouched).unwrap());
+    }
+
+    #[test]
+    fn test_records_cache_parses_shared_record_once() {
+        let mut phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380014, "北京|北京|100000|010", 2),
+            (1380755, "广东|深圳|518000|0755", 3),
+        ]);
+        // 关闭结果缓存，确认记录缓存独立生效
+        phone_data.cache_enabled = false;
+
+        let first = phone_data.find("13800138000").unwrap();
+        assert_eq!(phone_data.stats.record_parses.load(Ordering::Relaxed), 1);
+        let second = phone_data.find("13800148000").unwrap();
+        assert_eq!(phone_data.stats.record_parses.load(Ordering::Relaxed), 1);
+        assert_eq!(first.city, second.city);
+        assert_ne!(first.card_type, second.card_type);
+
+        phone_data.find("13807550000").unwrap();
+        phone_data.find("13800138000").unwrap();
+        assert_eq!(phone_data.stats.record_parses.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_find_interned_shares_city_storage() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380014, "北京|北京|100001|010", 2),
+        ]);
+
+        let first = phone_data.find_interned("13800138000").unwrap();
+        let second = phone_data.find_interned("13800148000").unwrap();
+        assert_eq!(&*first.city, "北京");
+        assert_eq!(first.card_type, "中国移动");
+        assert_eq!(second.card_type, "中国联通");
+        assert_ne!(first.zip_code, second.zip_code);
+        assert!(Arc::ptr_eq(&first.city, &second.city));
+        assert!(Arc::ptr_eq(&first.province, &second.province));
+    }
+
+    #[test]
+    fn test_find_grouped_by_region() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380755, "广东|深圳|518000|0755", 1),
+            (1380756, "广东|广州|510000|020", 2),
+        ]);
+        let numbers = [
+            "13800138000",
+            "13807550000",
+            "13807560000",
+            "13800130001",
+            "123",
+        ];
+
+        let by_province = phone_data.find_grouped(&numbers, GroupBy::Province);
+        assert_eq!(by_province.groups.len(), 2);
+        assert_eq!(by_province.groups["北京"].len(), 2);
+        assert_eq!(by_province.groups["广东"].len(), 2);
+        assert_eq!(by_province.errors, vec!["123".to_string()]);
+
+        let by_city = phone_data.find_grouped(&numbers, GroupBy::City);
+        assert_eq!(by_city.groups.len(), 3);
+        assert_eq!(by_city.groups["广东/深圳"][0].area_code, "0755");
+        assert_eq!(by_city.groups["广东/广州"][0].card_type, "中国联通");
+    }
+
+    #[test]
+    fn test_cache_health_ineffective_for_diverse_queries() {
+        let phone_data = create_mock_phone_data_with(&[(1380013, "北京|北京|100000|010", 1)]);
+        assert_eq!(phone_data.cache_health(), CacheHealth::Warming);
+
+        // 每个号码只查询一次，缓存永远不会命中
+        for suffix in 0..CACHE_HEALTH_MIN_QUERIES {
+            phone_data.find(&format!("1380013{:04}", suffix)).unwrap();
         }
+        assert_eq!(phone_data.cache_health(), CacheHealth::Ineffective);
+        assert!(phone_data.stats.cache_health_warned.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_cache_health_healthy_for_repeated_queries() {
+        let phone_data = create_mock_phone_data_with(&[(1380013, "北京|北京|100000|010", 1)]);
+        for _ in 0..CACHE_HEALTH_MIN_QUERIES {
+            phone_data.find("13800138000").unwrap();
+        }
+        assert_eq!(phone_data.cache_health(), CacheHealth::Healthy);
+    }
+
+    #[test]
+    fn test_city_fallback() {
+        let entries = [
+            (1380010, "广东|珠海|519000|0756", 1),
+            (1380012, "广东|深圳|518000|0755", 1),
+            (1380013, "广东||510000|020", 1),
+            (1380014, "广东||510000|020", 1),
+            (1380015, "广西|南宁|530000|0771", 1),
+        ];
+
+        let mut phone_data = create_mock_phone_data_with(&entries);
+        assert_eq!(phone_data.find("13800130000").unwrap().city, "");
+
+        phone_data = create_mock_phone_data_with(&entries);
+        phone_data.city_fallback = CityFallback::Province;
+        assert_eq!(phone_data.find("13800130000").unwrap().city, "广东");
+
+        phone_data = create_mock_phone_data_with(&entries);
+        phone_data.city_fallback = CityFallback::NearestPrefix;
+        assert_eq!(phone_data.find("13800130000").unwrap().city, "深圳");
+        // 右侧邻居属于其他省份，只能向左查找
+        assert_eq!(phone_data.find("13800140000").unwrap().city, "深圳");
+    }
+
+    #[test]
+    fn test_unknown_card_types() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380014, "北京|北京|100000|010", 9),
+            (1380015, "北京|北京|100000|010", 0),
+            (1920000, "广东|佛山|528000|0757", 8),
+        ]);
+        assert_eq!(phone_data.unknown_card_types(), vec![(1380014, 9)]);
+        assert!(matches!(
+            phone_data.find("13800140000"),
+            Err(ErrorKind::InvalidOpNo)
+        ));
+
+        // 运营商代码 0 降级为未知运营商，地理信息照常返回
+        let info = phone_data.find("13800150000").unwrap();
+        assert_eq!(info.province, "北京");
+        assert_eq!(info.card_type, "未知运营商");
+        assert_eq!(info.icon_key, "unknown");
+        let typed = phone_data.find_typed("13800150000").unwrap();
+        assert_eq!(typed.carrier, CardType::Unknown);
+        assert!(!typed.is_virtual);
+        assert_eq!(
+            phone_data
+                .find_localized("13800150000", Locale::En)
+                .unwrap()
+                .card_type,
+            "Unknown Carrier"
+        );
+    }
+
+    #[test]
+    fn test_is_plausible_mobile() {
+        assert!(is_plausible_mobile("13800138000"));
+        assert!(is_plausible_mobile("1920000"));
+        assert!(!is_plausible_mobile("10086000000"));
+        assert!(!is_plausible_mobile("23800138000"));
+        assert!(!is_plausible_mobile("1380013800a"));
+        assert!(!is_plausible_mobile("138"));
+    }
+
+    #[test]
+    fn test_reject_implausible_skips_search() {
+        let mut phone_data = create_mock_phone_data_with(&[(1380013, "北京|北京|100000|010", 1)]);
+        phone_data.reject_implausible = true;
+
+        assert!(matches!(
+            phone_data.find("11100000000"),
+            Err(ErrorKind::NotFound)
+        ));
+        assert_eq!(phone_data.index_searches(), 0);
+
+        assert!(phone_data.find("13800138000").is_ok());
+        assert_eq!(phone_data.index_searches(), 1);
+
+        phone_data.reject_implausible = false;
+        assert!(matches!(
+            phone_data.find("11100000000"),
+            Err(ErrorKind::NotFound)
+        ));
+        assert_eq!(phone_data.index_searches(), 2);
+    }
+
+    #[cfg(feature = "camel-case")]
+    #[test]
+    fn test_cache_stats_camel_case() {
+        let stats = create_mock_phone_data().get_cache_stats();
+        let json = serde_json::to_string(&stats).unwrap();
+        assert!(json.contains("\"totalQueries\":0"));
+        assert!(json.contains("\"maxSize\":100"));
+    }
+
+    #[cfg(not(feature = "camel-case"))]
+    #[test]
+    fn test_cache_stats_snake_case() {
+        let stats = create_mock_phone_data().get_cache_stats();
+        let json = serde_json::to_string(&stats).unwrap();
+        assert!(json.contains("\"total_queries\":0"));
+        assert!(json.contains("\"max_size\":100"));
+    }
+
+    #[test]
+    fn test_miss_and_not_found_counters() {
+        let phone_data = create_mock_phone_data_with(&[(1380013, "北京|北京|100000|010", 1)]);
+        phone_data.find("13800138000").unwrap();
+        phone_data.find("13800138000").unwrap();
+        assert!(phone_data.find("13900000000").is_err());
+
+        let stats = phone_data.get_cache_stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 2);
+        assert_eq!(stats.not_found, 1);
+        assert_eq!(stats.total_queries, 3);
+        assert!((phone_data.not_found_rate() - 100.0 / 3.0).abs() < 1e-9);
+
+        // 长度无效不算未找到
+        assert!(phone_data.find("123").is_err());
+        assert_eq!(phone_data.not_found_count(), 1);
+    }
+
+    #[test]
+    fn test_phone_data_is_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<PhoneData>();
+        assert_send_sync::<Arc<PhoneData>>();
+    }
+
+    #[test]
+    fn test_clone_shares_stats_with_cache() {
+        let phone_data = create_mock_phone_data_with(&[(1380013, "北京|北京|100000|010", 1)]);
+        phone_data.find("13800138000").unwrap();
+
+        let cloned = phone_data.clone();
+        cloned.find("13800138000").unwrap();
+        assert_eq!(phone_data.query_count(), 2);
+        assert_eq!(phone_data.cache_hits(), 1);
+        assert_eq!(cloned.get_cache_stats().hits, 1);
+        assert_eq!(cloned.get_cache_stats().size, 1);
+    }
+
+    #[test]
+    fn test_slow_query_logged_while_cache_lock_held() {
+        let mut phone_data = create_mock_phone_data_with(&[(1380013, "北京|北京|100000|010", 1)]);
+        phone_data.slow_query_threshold = Some(Duration::from_millis(20));
+        let phone_data = Arc::new(phone_data);
+
+        phone_data.find("13800138000").unwrap();
+        assert_eq!(phone_data.slow_queries(), 0);
+
+        // 持有缓存写锁制造锁竞争，find 读取缓存时会被阻塞
+        let (locked_tx, locked_rx) = std::sync::mpsc::channel();
+        let holder = {
+            let phone_data = phone_data.clone();
+            std::thread::spawn(move || {
+                let _guard = phone_data.cache.write().unwrap();
+                locked_tx.send(()).unwrap();
+                std::thread::sleep(Duration::from_millis(50));
+            })
+        };
+        locked_rx.recv().unwrap();
+        phone_data.find("13800138000").unwrap();
+        holder.join().unwrap();
+
+        assert_eq!(phone_data.slow_queries(), 1);
+    }
+
+    #[test]
+    fn test_mask_number() {
+        assert_eq!(mask_number("13800138000"), "138****8000");
+        assert_eq!(mask_number("1380013800"), "138****800");
+        assert_eq!(mask_number("138001380"), "138****80");
+        assert_eq!(mask_number("13800138"), "138****8");
+        assert_eq!(mask_number("1380013"), "138****");
+    }
+
+    #[test]
+    fn test_find_batch_by_carrier() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1380011, "北京|北京|100000|010", 1),
+            (1380012, "北京|北京|100000|010", 2),
+            (1380016, "北京|北京|100000|010", 6),
+        ]);
+        let numbers = [
+            "13800110000",
+            "13800120000",
+            "13800160000",
+            "13900000000",
+            "12",
+        ];
+        let icon_keys = |results: Vec<Option<PhoneNoInfo>>| -> Vec<Option<String>> {
+            results
+                .into_iter()
+                .map(|info| info.map(|info| info.icon_key))
+                .collect()
+        };
+
+        assert_eq!(
+            icon_keys(phone_data.find_batch_by_carrier(&numbers, CardType::Cmcc, false)),
+            vec![Some("cmcc".to_string()), None, None, None, None]
+        );
+        assert_eq!(
+            icon_keys(phone_data.find_batch_by_carrier(&numbers, CardType::Cmcc, true)),
+            vec![
+                Some("cmcc".to_string()),
+                None,
+                Some("cmcc_v".to_string()),
+                None,
+                None
+            ]
+        );
+        // 指定虚拟运营商时不会匹配实网运营商
+        assert_eq!(
+            icon_keys(phone_data.find_batch_by_carrier(&numbers, CardType::CmccV, true)),
+            vec![None, None, Some("cmcc_v".to_string()), None, None]
+        );
+        assert_eq!(CardType::CbccV.network(), CardType::Cbcc);
+        assert_eq!(CardType::Cucc.network(), CardType::Cucc);
+    }
+
+    #[test]
+    fn test_find_typed_all_card_types() {
+        let entries: Vec<(i32, &str, u8)> = (1..=8)
+            .map(|card| (1380010 + card as i32, "北京|北京|100000|010", card))
+            .collect();
+        let phone_data = create_mock_phone_data_with(&entries);
+        let expected = [
+            (CardType::Cmcc, "中国移动", "China Mobile", false),
+            (CardType::Cucc, "中国联通", "China Unicom", false),
+            (CardType::Ctcc, "中国电信", "China Telecom", false),
+            (
+                CardType::CtccV,
+                "中国电信虚拟运营商",
+                "China Telecom MVNO",
+                true,
+            ),
+            (
+                CardType::CuccV,
+                "中国联通虚拟运营商",
+                "China Unicom MVNO",
+                true,
+            ),
+            (
+                CardType::CmccV,
+                "中国移动虚拟运营商",
+                "China Mobile MVNO",
+                true,
+            ),
+            (CardType::Cbcc, "中国广电", "China Broadnet", false),
+            (
+                CardType::CbccV,
+                "中国广电虚拟运营商",
+                "China Broadnet MVNO",
+                true,
+            ),
+        ];
+        for (i, (carrier, zh, en, is_virtual)) in expected.into_iter().enumerate() {
+            let no = format!("138001{}0000", i + 1);
+            let typed = phone_data.find_typed(&no).expect(&no);
+            assert_eq!(typed.carrier, carrier);
+            assert_eq!(typed.is_virtual, is_virtual);
+            assert_eq!(typed.province, "北京");
+            assert_eq!(carrier.to_string(), zh);
+            assert_eq!(carrier.as_str_en(), en);
+        }
+        assert!(matches!(
+            phone_data.find_typed("13900000000"),
+            Err(ErrorKind::NotFound)
+        ));
+    }
+
+    #[test]
+    fn test_find_batch_keeps_order() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380755, "广东|深圳|518000|0755", 3),
+        ]);
+        let numbers = [
+            "13807550000",
+            "123",
+            "13800138000",
+            "13900000000",
+            "13807550000",
+        ];
+
+        let results = phone_data.find_batch(&numbers);
+        assert_eq!(results.len(), numbers.len());
+        assert_eq!(results[0].as_ref().unwrap().city, "深圳");
+        assert!(matches!(results[1], Err(ErrorKind::InvalidLength)));
+        assert_eq!(results[2].as_ref().unwrap().city, "北京");
+        assert!(matches!(results[3], Err(ErrorKind::NotFound)));
+        assert_eq!(results[4].as_ref().unwrap().city, "深圳");
+        // 重复号码命中缓存
+        assert_eq!(phone_data.cache_hits(), 1);
+
+        let mapped = phone_data.find_batch_map(&numbers);
+        let phones: Vec<&str> = mapped.iter().map(|(no, _)| no.as_str()).collect();
+        assert_eq!(phones, numbers);
+        assert!(mapped[2].1.is_ok());
+        assert!(mapped[3].1.is_err());
+    }
+
+    #[test]
+    fn test_normalize_number() {
+        for input in [
+            "13800138000",
+            "+86 138 0013 8000",
+            "+8613800138000",
+            "86-138-0013-8000",
+            "8613800138000",
+            "0086 13800138000",
+            "(+86) 138-0013-8000",
+            " 138 0013 8000 ",
+        ] {
+            assert_eq!(
+                normalize_number(input).as_deref(),
+                Some("13800138000"),
+                "{}",
+                input
+            );
+        }
+        assert_eq!(
+            normalize_number("(010) 1234-5678").as_deref(),
+            Some("01012345678")
+        );
+        assert_eq!(normalize_number("1380013800a"), None);
+        assert_eq!(normalize_number("+1 415 555 0100"), None);
+        assert_eq!(normalize_number("+86"), None);
+        assert_eq!(normalize_number("138+0013"), None);
+
+        let phone_data = create_mock_phone_data_with(&[(1380013, "北京|北京|100000|010", 1)]);
+        assert_eq!(
+            phone_data
+                .find_normalized("+86 138-0013-8000")
+                .unwrap()
+                .city,
+            "北京"
+        );
+        assert!(matches!(
+            phone_data.find_normalized("+86 138"),
+            Err(ErrorKind::InvalidLength)
+        ));
+        assert!(matches!(
+            phone_data.find_normalized("+86 138 0013 8000 12"),
+            Err(ErrorKind::InvalidLength)
+        ));
+        assert!(matches!(
+            phone_data.find_normalized("138.0013.8000"),
+            Err(ErrorKind::InvalidCharacter {
+                position: 3,
+                found: '.'
+            })
+        ));
+        assert!(matches!(
+            phone_data.find_normalized("138+0013"),
+            Err(ErrorKind::InvalidCharacter {
+                position: 3,
+                found: '+'
+            })
+        ));
+        assert!(matches!(
+            phone_data.find_normalized("+1 415 555 0100"),
+            Err(ErrorKind::InvalidCharacter {
+                position: 0,
+                found: '+'
+            })
+        ));
+        assert!(matches!(
+            phone_data.find_normalized("+86"),
+            Err(ErrorKind::InvalidLength)
+        ));
+    }
+
+    #[test]
+    fn test_find_area_and_zip_code() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380014, "未知|未知||", 1),
+        ]);
+        assert_eq!(phone_data.find_area_code("13800138000").unwrap(), "010");
+        assert_eq!(phone_data.find_zip_code("13800138000").unwrap(), "100000");
+        assert!(matches!(
+            phone_data.find_area_code("13800148000"),
+            Err(ErrorKind::NotFound)
+        ));
+        assert!(matches!(
+            phone_data.find_zip_code("13800148000"),
+            Err(ErrorKind::NotFound)
+        ));
+        assert!(matches!(
+            phone_data.find_zip_code("123"),
+            Err(ErrorKind::InvalidLength)
+        ));
+    }
+
+    #[test]
+    fn test_prefixes_by_province() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380755, "广东|深圳|518000|0755", 3),
+            (1380756, "广东|广州|510000|020", 3),
+            (1390013, "北京|北京|100000|010", 2),
+        ]);
+        let prefixes = phone_data.prefixes_by_province("广东");
+        assert_eq!(prefixes, vec![1380755, 1380756]);
+        for prefix in phone_data.prefixes_by_province("北京") {
+            let no = format!("{}0000", prefix);
+            assert_eq!(phone_data.find_province(&no).unwrap(), "北京");
+        }
+        assert_eq!(phone_data.prefixes_by_province("北京").len(), 2);
+        assert!(phone_data.prefixes_by_province("火星").is_empty());
+    }
+
+    #[test]
+    fn test_iter_yields_every_prefix_in_order() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380014, "北京|北京|100000|010", 2),
+            (1380755, "广东|深圳|518000|0755", 3),
+            (1390013, "北京|北京|100000|010", 7),
+        ]);
+        let entries: Vec<(u32, PhoneNoInfo)> = phone_data.iter().collect();
+        assert_eq!(entries.len(), phone_data.index_count());
+        assert!(entries.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert_eq!(entries[1].0, 1380014);
+        assert_eq!(entries[1].1.card_type, "中国联通");
+        assert_eq!(entries[3].1.city, "北京");
+        assert_eq!(phone_data.query_count(), 0);
+
+        let mut iter = phone_data.iter();
+        assert_eq!(iter.next().unwrap().0, 1380013);
+    }
+
+    #[test]
+    fn test_find_with_segment_requires_exact_prefix() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380755, "广东|深圳|518000|0755", 3),
+        ]);
+        let (info, segment) = phone_data.find_with_segment("13807551234").unwrap();
+        assert_eq!(segment, 1380755);
+        assert_eq!(info.city, "深圳");
+        assert_eq!(phone_data.find_with_segment("1380013").unwrap().1, 1380013);
+
+        // 落在两个号段之间的号码不匹配任何一侧
+        assert!(matches!(
+            phone_data.find_with_segment("13800140000"),
+            Err(ErrorKind::NotFound)
+        ));
+        assert!(matches!(
+            phone_data.find_with_segment("13807540000"),
+            Err(ErrorKind::NotFound)
+        ));
+    }
+
+    #[test]
+    fn test_validate_number() {
+        let phone_data = create_mock_phone_data_with(&[(1380013, "北京|北京|100000|010", 1)]);
+        let cases = [
+            ("13800138000", NumberValidity::Valid),
+            ("1380013", NumberValidity::Valid),
+            ("13900000000", NumberValidity::UnknownSegment),
+            ("01012345678", NumberValidity::UnknownSegment),
+            ("138001", NumberValidity::TooShort),
+            ("", NumberValidity::TooShort),
+            ("138001380001", NumberValidity::TooLong),
+            ("1380013800a", NumberValidity::NonDigit),
+            ("138 0013", NumberValidity::NonDigit),
+            ("12a", NumberValidity::NonDigit),
+        ];
+        for (no, expected) in cases {
+            assert_eq!(phone_data.validate_number(no), expected, "{}", no);
+        }
+        assert_eq!(phone_data.get_cache_stats().total_queries, 0);
+        assert_eq!(phone_data.get_cache_stats().size, 0);
+    }
+
+    #[test]
+    fn test_classify_number() {
+        assert_eq!(classify_number("13800138000"), NumberKind::Mobile);
+        assert_eq!(classify_number("075512345678"), NumberKind::Landline);
+        assert_eq!(classify_number("01012345678"), NumberKind::Landline);
+        assert_eq!(classify_number("1380013"), NumberKind::Unknown);
+        assert_eq!(classify_number("0755123"), NumberKind::Unknown);
+        assert_eq!(classify_number("1380013800a"), NumberKind::Unknown);
+    }
+
+    /// 按 `phone.dat` 格式编码数据库，相同记录共享偏移
+    pub(crate) fn encode_database(version: &str, entries: &[(i32, &str, u8)]) -> Vec<u8> {
+        let mut records = Vec::new();
+        let mut offsets: HashMap<&str, i32> = HashMap::new();
+        let mut index = Vec::new();
+        for &(prefix, record, card_type) in entries {
+            let offset = *offsets.entry(record).or_insert_with(|| {
+                let offset = records.len() as i32 + 8;
+                records.extend_from_slice(record.as_bytes());
+                records.push(0);
+                offset
+            });
+            index.extend_from_slice(&prefix.to_le_bytes());
+            index.extend_from_slice(&offset.to_le_bytes());
+            index.push(card_type);
+        }
+
+        let mut bytes = version.as_bytes()[..4].to_vec();
+        bytes.extend_from_slice(&(records.len() as i32 + 8).to_le_bytes());
+        bytes.extend_from_slice(&records);
+        bytes.extend_from_slice(&index);
+        bytes
+    }
+
+    #[test]
+    fn test_custom_record_delimiter() {
+        let bytes = encode_database(
+            "2502",
+            &[
+                (1380013, "北京,北京,100000,010", 1),
+                (1380755, "广东,深圳,518000,0755", 3),
+            ],
+        );
+
+        let phone_data = PhoneData::builder()
+            .record_delimiter(',')
+            .build_from_bytes(&bytes)
+            .unwrap();
+        let info = phone_data.find("13807550000").unwrap();
+        assert_eq!(info.province, "广东");
+        assert_eq!(info.city, "深圳");
+        assert_eq!(info.area_code, "0755");
+        assert_eq!(info.card_type, "中国电信");
+
+        // 默认分隔符无法解析这种记录
+        let phone_data = PhoneData::from_bytes(&bytes).unwrap();
+        assert!(matches!(
+            phone_data.find("13807550000"),
+            Err(ErrorKind::InvalidPhoneDatabase)
+        ));
+    }
+
+    #[test]
+    fn test_invalid_record_delimiter() {
+        let bytes = encode_database("2502", &[(1380013, "北京|北京|100000|010", 1)]);
+        for delimiter in ['5', '\0'] {
+            let result = PhoneData::builder()
+                .record_delimiter(delimiter)
+                .build_from_bytes(&bytes);
+            assert!(matches!(result, Err(ErrorKind::InvalidDelimiter(d)) if d == delimiter));
+        }
+    }
+
+    #[test]
+    fn test_distinct_prefix_count() {
+        assert_eq!(create_mock_phone_data().distinct_prefix_count(), 0);
+
+        let phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380014, "北京|北京|100000|010", 1),
+            (1380014, "河北|石家庄|050000|0311", 1),
+            (1380015, "北京|北京|100000|010", 1),
+        ]);
+        assert_eq!(phone_data.index_count(), 4);
+        assert_eq!(phone_data.distinct_prefix_count(), 3);
+    }
+
+    #[test]
+    fn test_find_neighbors() {
+        let entries: Vec<(i32, &str, u8)> = (0..10)
+            .map(|i| (1380010 + i, "北京|北京|100000|010", 1))
+            .collect();
+        let phone_data = create_mock_phone_data_with(&entries);
+        let prefixes = |result: Vec<(u32, PhoneNoInfo)>| -> Vec<u32> {
+            result.into_iter().map(|(prefix, _)| prefix).collect()
+        };
+
+        let middle = phone_data.find_neighbors("1380015", 2).unwrap();
+        assert_eq!(
+            prefixes(middle),
+            vec![1380013, 1380014, 1380015, 1380016, 1380017]
+        );
+
+        let start = phone_data.find_neighbors("1380011", 3).unwrap();
+        assert_eq!(
+            prefixes(start),
+            vec![1380010, 1380011, 1380012, 1380013, 1380014]
+        );
+
+        let end = phone_data.find_neighbors("13800190000", 3).unwrap();
+        assert_eq!(prefixes(end), vec![1380016, 1380017, 1380018, 1380019]);
+
+        let single = phone_data.find_neighbors("1380012", 0).unwrap();
+        assert_eq!(single.len(), 1);
+        assert_eq!(single[0].1.province, "北京");
+
+        assert!(matches!(
+            phone_data.find_neighbors("1390000", 1),
+            Err(ErrorKind::NotFound)
+        ));
+    }
+
+    #[test]
+    fn test_version_date() {
+        let with_version = |version: &str| {
+            let phone_data = create_mock_phone_data_with(&[(1380013, "北京|北京|100000|010", 1)]);
+            PhoneData::from_bytes(&encode_phone_dat(&phone_data, version)).unwrap()
+        };
+        let date = |y, m, d| chrono::NaiveDate::from_ymd_opt(y, m, d);
+
+        let newer = with_version("2502");
+        let older = with_version("2412");
+        assert_eq!(newer.version_date(), date(2025, 2, 1));
+        assert_eq!(older.version_date(), date(2024, 12, 1));
+        assert!(newer.is_newer_than(&older));
+        assert!(!older.is_newer_than(&newer));
+        assert!(!newer.is_newer_than(&newer));
+
+        assert_eq!(parse_version_date("202502"), date(2025, 2, 1));
+        assert_eq!(parse_version_date("20250214"), date(2025, 2, 14));
+        for version in ["TEST", "2513", "25-2", "", "2502 x", "20250230"] {
+            assert_eq!(parse_version_date(version), None, "{}", version);
+        }
+
+        let unknown = with_version("TEST");
+        assert_eq!(unknown.version_date(), None);
+        assert!(!unknown.is_newer_than(&older));
+        assert!(!newer.is_newer_than(&unknown));
+    }
+
+    #[test]
+    fn test_search_by_city() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1380010, "广东|深圳|518000|0755", 1),
+            (1380011, "广东|广州|510000|020", 2),
+            (1380012, "广东||510000|020", 1),
+            (1380013, "广东|深圳|518000|0755", 3),
+            (1380014, "内蒙古|呼和浩特|010000|0471", 1),
+            (1380015, "广东|深圳|518000|0755", 9),
+        ]);
+
+        let results = phone_data.search_by_city("深圳");
+        let prefixes: Vec<u32> = results.iter().map(|(prefix, _)| *prefix).collect();
+        assert_eq!(prefixes, vec![1380010, 1380013]);
+        assert!(results.iter().all(|(_, info)| info.city.contains("深圳")));
+        assert_eq!(results[1].1.card_type, "中国电信");
+        assert!(phone_data.snapshot().city_positions.get().is_some());
+
+        // 子串匹配
+        let results = phone_data.search_by_city("州");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].1.city, "广州");
+        let results = phone_data.search_by_city("浩");
+        assert_eq!(results[0].0, 1380014);
+
+        assert!(phone_data.search_by_city("上海").is_empty());
+        assert!(phone_data.search_by_city("").is_empty());
+        assert_eq!(phone_data.query_count(), 0);
+
+        let phone_data = PhoneData::new().unwrap();
+        let results = phone_data.search_by_city("州");
+        assert!(results.len() > 100);
+        assert!(results.iter().all(|(_, info)| info.city.contains('州')));
+        assert!(results.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn test_find_nearest() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1380010, "北京|北京|100000|010", 1),
+            (1380020, "广东|深圳|518000|0755", 2),
+            (1380100, "上海|上海|200000|021", 3),
+        ]);
+
+        let (prefix, info, diff) = phone_data.find_nearest("13800200000").unwrap();
+        assert_eq!((prefix, diff), (1380020, 0));
+        assert_eq!(info.city, "深圳");
+
+        // 间隙中靠近上侧、靠近下侧以及距离相等的情况
+        let (prefix, info, diff) = phone_data.find_nearest("1380090").unwrap();
+        assert_eq!((prefix, diff), (1380100, 10));
+        assert_eq!(info.city, "上海");
+        let (prefix, _, diff) = phone_data.find_nearest("1380023").unwrap();
+        assert_eq!((prefix, diff), (1380020, -3));
+        let (prefix, _, diff) = phone_data.find_nearest("1380015").unwrap();
+        assert_eq!((prefix, diff), (1380010, -5));
+
+        // 索引两端只有一侧候选
+        let (prefix, _, diff) = phone_data.find_nearest("13000000000").unwrap();
+        assert_eq!((prefix, diff), (1380010, 80010));
+        let (prefix, _, diff) = phone_data.find_nearest("19900000000").unwrap();
+        assert_eq!((prefix, diff), (1380100, 1380100 - 1990000));
+        assert_eq!(phone_data.query_count(), 0);
+
+        assert!(matches!(
+            create_mock_phone_data().find_nearest("13800138000"),
+            Err(ErrorKind::NotFound)
+        ));
+        assert!(matches!(
+            phone_data.find_nearest("138"),
+            Err(ErrorKind::InvalidLength)
+        ));
+    }
+
+    #[cfg(feature = "embed-data")]
+    #[test]
+    fn test_embedded_database() {
+        let phone_data = PhoneData::embedded().unwrap();
+        assert_eq!(phone_data.version(), "2502");
+        assert_eq!(phone_data.find("13800138000").unwrap().province, "北京");
+    }
+
+    #[test]
+    fn test_find_province_matches_find() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380755, "广东|深圳|518000|0755", 2),
+        ]);
+        for no in ["13800138000", "13807550000"] {
+            assert_eq!(
+                phone_data.find_province(no).unwrap(),
+                phone_data.find(no).unwrap().province
+            );
+        }
+        assert!(matches!(
+            phone_data.find_province("1390000"),
+            Err(ErrorKind::NotFound)
+        ));
+        assert!(matches!(
+            phone_data.find_province("138"),
+            Err(ErrorKind::InvalidLength)
+        ));
+    }
+
+    #[test]
+    fn test_landline_resolves_by_area_code() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380755, "广东|深圳|518000|0755", 1),
+            (1380756, "广东|深圳|518000|0755", 2),
+        ]);
+
+        let info = phone_data.find("075512345678").unwrap();
+        assert_eq!(info.city, "深圳");
+        assert_eq!(info.area_code, "0755");
+        assert_eq!(info.card_type, LANDLINE_CARD_TYPE);
+        assert_eq!(info.icon_key, LANDLINE_ICON_KEY);
+
+        assert_eq!(phone_data.find("01012345678").unwrap().city, "北京");
+        assert_eq!(
+            phone_data.find_by_area_code("0755").unwrap().province,
+            "广东"
+        );
+        assert!(matches!(
+            phone_data.find("057112345678"),
+            Err(ErrorKind::NotFound)
+        ));
+        assert!(matches!(
+            phone_data.find("07551234"),
+            Err(ErrorKind::InvalidLength)
+        ));
+        assert!(matches!(
+            phone_data.find("0755-12345678"),
+            Err(ErrorKind::InvalidCharacter {
+                position: 4,
+                found: '-'
+            })
+        ));
+    }
+
+    #[test]
+    fn test_custom_carrier_label() {
+        let bytes = encode_database(
+            "2502",
+            &[
+                (1380013, "北京|北京|100000|010", 1),
+                (1380014, "北京|北京|100000|010", 2),
+            ],
+        );
+        let phone_data = PhoneData::builder()
+            .carrier_label(CardType::Cmcc, "China Mobile")
+            .build_from_bytes(&bytes)
+            .unwrap();
+
+        assert_eq!(
+            phone_data.find("13800138000").unwrap().card_type,
+            "China Mobile"
+        );
+        assert_eq!(
+            phone_data.find("13800148000").unwrap().card_type,
+            "中国联通"
+        );
+    }
+
+    #[test]
+    fn test_set_carrier_labels() {
+        let mut phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380014, "北京|北京|100000|010", 2),
+        ]);
+        assert_eq!(
+            phone_data.find("13800138000").unwrap().card_type,
+            "中国移动"
+        );
+
+        phone_data.set_carrier_labels(HashMap::from([(CardType::Cmcc, "移动".to_string())]));
+        let info = phone_data.find("13800138000").unwrap();
+        assert_eq!(info.card_type, "移动");
+        assert_eq!(info.icon_key, "cmcc");
+        assert_eq!(
+            phone_data.find("13800148000").unwrap().card_type,
+            "中国联通"
+        );
+
+        phone_data.set_carrier_labels(HashMap::new());
+        assert_eq!(
+            phone_data.find("13800138000").unwrap().card_type,
+            "中国移动"
+        );
+
+        // 修改名称后不再共享缓存，其他克隆写入的旧名称结果不会被读到
+        let other = phone_data.clone();
+        phone_data.set_carrier_labels(HashMap::from([(CardType::Cmcc, "移动".to_string())]));
+        assert_eq!(other.find("13800138000").unwrap().card_type, "中国移动");
+        assert_eq!(phone_data.find("13800138000").unwrap().card_type, "移动");
+        assert_eq!(other.find("13800138000").unwrap().card_type, "中国移动");
+    }
+
+    #[cfg(feature = "timezone")]
+    #[test]
+    fn test_timezone_populated() {
+        let phone_data = create_mock_phone_data_with(&[(1380755, "广东|深圳|518000|0755", 1)]);
+        assert_eq!(
+            phone_data.find("13807550000").unwrap().timezone,
+            "Asia/Shanghai"
+        );
+        assert_eq!(
+            phone_data.find("075512345678").unwrap().timezone,
+            "Asia/Shanghai"
+        );
+    }
+
+    #[test]
+    fn test_resolve_resource_path_order() {
+        let root =
+            std::env::temp_dir().join(format!("phone_lookup_resource_{}", std::process::id()));
+        let (first, second) = (root.join("first"), root.join("second"));
+        std::fs::create_dir_all(&first).unwrap();
+        std::fs::create_dir_all(&second).unwrap();
+        let env_file = root.join("env.dat");
+        std::fs::write(&env_file, b"").unwrap();
+        std::fs::write(second.join("phone.dat"), b"").unwrap();
+        let dirs = [first.clone(), second.clone()];
+        let env_path = env_file.to_str().unwrap();
+
+        // 环境变量优先于搜索目录
+        assert_eq!(
+            resolve_resource_path("phone.dat", Some(env_path), &dirs),
+            Ok(env_path.to_string())
+        );
+        // 环境变量无效时按目录顺序查找
+        let found = resolve_resource_path("phone.dat", Some("/nonexistent/phone.dat"), &dirs);
+        assert_eq!(
+            found,
+            Ok(second.join("phone.dat").to_string_lossy().into_owned())
+        );
+        std::fs::write(first.join("phone.dat"), b"").unwrap();
+        let found = resolve_resource_path("phone.dat", None, &dirs);
+        assert_eq!(
+            found,
+            Ok(first.join("phone.dat").to_string_lossy().into_owned())
+        );
+
+        // 全部找不到时列出尝试过的所有路径
+        let tried = resolve_resource_path("missing.dat", Some("/nonexistent/phone.dat"), &dirs)
+            .unwrap_err();
+        assert_eq!(
+            tried,
+            vec![
+                "/nonexistent/phone.dat".to_string(),
+                first.join("missing.dat").to_string_lossy().into_owned(),
+                second.join("missing.dat").to_string_lossy().into_owned(),
+            ]
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let err = get_resource_path("missing-resource.dat").unwrap_err();
+        assert!(err.to_string().contains("missing-resource.dat"));
+    }
+
+    #[test]
+    fn test_check_env_data_path() {
+        assert_eq!(check_env_data_path(None), EnvDataPath::Unset);
+        assert_eq!(check_env_data_path(Some("")), EnvDataPath::Blank);
+        assert_eq!(check_env_data_path(Some("  \t ")), EnvDataPath::Blank);
+        assert_eq!(
+            check_env_data_path(Some(".")),
+            EnvDataPath::NotAFile(".".to_string())
+        );
+        assert_eq!(
+            check_env_data_path(Some(" /nonexistent/phone.dat ")),
+            EnvDataPath::NotAFile("/nonexistent/phone.dat".to_string())
+        );
+
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/phone.dat");
+        assert_eq!(
+            check_env_data_path(Some(&format!(" {}\n", path))),
+            EnvDataPath::Found(path.to_string())
+        );
+    }
+
+    #[test]
+    fn test_find_quiet_skips_stats_and_cache() {
+        let phone_data = create_mock_phone_data_with(&[(1380013, "北京|北京|100000|010", 1)]);
+        let calls = Arc::new(AtomicU64::new(0));
+        let observed = calls.clone();
+        phone_data.set_query_observer(move |_, _| {
+            observed.fetch_add(1, Ordering::Relaxed);
+        });
+
+        for _ in 0..3 {
+            assert_eq!(
+                phone_data.find_quiet("13800138000").unwrap(),
+                phone_data.find_quiet("13800138000").unwrap()
+            );
+        }
+        assert!(matches!(
+            phone_data.find_quiet("13900000000"),
+            Err(ErrorKind::NotFound)
+        ));
+        assert!(matches!(
+            phone_data.find_quiet("123"),
+            Err(ErrorKind::InvalidLength)
+        ));
+        assert_eq!(phone_data.query_count(), 0);
+        assert_eq!(phone_data.cache_hits(), 0);
+        assert_eq!(phone_data.index_searches(), 0);
+        assert_eq!(phone_data.cache.read().unwrap().len(), 0);
+        assert_eq!(phone_data.cache_misses(), 0);
+        assert_eq!(calls.load(Ordering::Relaxed), 0);
+
+        let info = phone_data.find("13800138000").unwrap();
+        assert_eq!(phone_data.find_quiet("13800138000").unwrap(), info);
+        assert_eq!(phone_data.query_count(), 1);
+    }
+
+    #[test]
+    fn test_find_ref_matches_find() {
+        let entries = [
+            (1380010, "广东|珠海|519000|0756", 1),
+            (1380012, "广东|深圳|518000|0755", 2),
+            (1380013, "广东||510000|020", 7),
+            (1380015, "广西|南宁|530000|0771", 8),
+        ];
+        for fallback in [
+            CityFallback::None,
+            CityFallback::Province,
+            CityFallback::NearestPrefix,
+        ] {
+            let mut phone_data = create_mock_phone_data_with(&entries);
+            phone_data.city_fallback = fallback;
+            phone_data.set_carrier_labels(HashMap::from([(CardType::Cucc, "联通".to_string())]));
+            for no in ["13800100000", "13800120000", "13800130000", "13800159999"] {
+                let info = phone_data.find_ref(no).unwrap();
+                assert_eq!(info.to_info(), phone_data.find_quiet(no).unwrap(), "{}", no);
+            }
+        }
+
+        let phone_data = create_mock_phone_data_with(&entries);
+        let info = phone_data.find_ref("13800120000").unwrap();
+        assert_eq!(info.province(), "广东");
+        assert_eq!(info.city(), "深圳");
+        assert_eq!(info.zip_code(), "518000");
+        assert_eq!(info.area_code(), "0755");
+        assert_eq!(info.carrier(), CardType::Cucc);
+        assert_eq!(info.icon_key(), "cucc");
+        assert_eq!(phone_data.query_count(), 1);
+        assert_eq!(phone_data.cache.read().unwrap().len(), 0);
+
+        assert!(matches!(
+            phone_data.find_ref("13800110000"),
+            Err(ErrorKind::NotFound)
+        ));
+        assert!(matches!(
+            phone_data.find_ref("138"),
+            Err(ErrorKind::InvalidLength)
+        ));
+        assert!(matches!(
+            phone_data.find_ref("13a00120000"),
+            Err(ErrorKind::InvalidCharacter { .. })
+        ));
+    }
+
+    #[test]
+    fn test_find_batch_searches_each_prefix_once() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380755, "广东|深圳|518000|0755", 3),
+        ]);
+        let numbers: Vec<String> = (0..3000)
+            .map(|i| match i % 3 {
+                0 => format!("1380013{:04}", i),
+                1 => format!("1380755{:04}", i),
+                _ => format!("1390000{:04}", i),
+            })
+            .collect();
+        let numbers: Vec<&str> = numbers.iter().map(String::as_str).collect();
+
+        let results = phone_data.find_batch(&numbers);
+        assert_eq!(phone_data.index_searches(), 3);
+        assert_eq!(phone_data.query_count(), 3000);
+        for (i, result) in results.iter().enumerate() {
+            match i % 3 {
+                0 => assert_eq!(result.as_ref().unwrap().city, "北京"),
+                1 => assert_eq!(result.as_ref().unwrap().city, "深圳"),
+                _ => assert!(matches!(result, Err(ErrorKind::NotFound))),
+            }
+        }
+
+        // 同一号段复用的结果也以完整号码为键写入缓存
+        let fresh = create_mock_phone_data_with(&[(1380013, "北京|北京|100000|010", 1)]);
+        fresh.find_batch(&["13800130001", "13800130002", "13800130003", "13900000000"]);
+        assert_eq!(fresh.index_searches(), 2);
+        assert_eq!(fresh.get_cache_stats().size, 3);
+        fresh.find("13800130003").unwrap();
+        assert_eq!(fresh.cache_hits(), 1);
+
+        // find_batch_map 复用同样的按号段去重
+        let searches = phone_data.index_searches();
+        let mapped = phone_data.find_batch_map(&["13800130001", "13800130002", "138"]);
+        assert_eq!(phone_data.index_searches(), searches + 1);
+        assert_eq!(mapped[1].0, "13800130002");
+        assert_eq!(mapped[1].1.as_ref().unwrap().city, "北京");
+        assert!(matches!(mapped[2], (_, Err(ErrorKind::InvalidLength))));
+    }
+
+    #[test]
+    fn test_find_batch_sorted_by_province() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380311, "河北|石家庄|050000|0311", 2),
+            (1380755, "广东|深圳|518000|0755", 3),
+            (1380756, "广东|广州|510000|020", 1),
+        ]);
+        let numbers = [
+            "13803110000",
+            "1390000",
+            "13807550000",
+            "13800138000",
+            "123",
+            "13807560000",
+        ];
+
+        let sorted = phone_data.find_batch_sorted(&numbers, SortKey::Province);
+        let order: Vec<&str> = sorted.iter().map(|(no, _)| no.as_str()).collect();
+        // 按省份名称排序（北京 < 广东 < 河北），同省份保持输入顺序，失败的号码排在最后
+        assert_eq!(
+            order,
+            vec![
+                "13800138000",
+                "13807550000",
+                "13807560000",
+                "13803110000",
+                "1390000",
+                "123"
+            ]
+        );
+        assert!(sorted[..4].iter().all(|(_, result)| result.is_ok()));
+        assert!(sorted[4..].iter().all(|(_, result)| result.is_err()));
+    }
+
+    #[test]
+    fn test_subset_by_province() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380311, "河北|石家庄|050000|0311", 2),
+            (1380755, "广东|深圳|518000|0755", 3),
+            (1380756, "广东|广州|510000|020", 1),
+            (1380757, "广东|深圳|518000|0755", 2),
+            (1380758, "广东|深圳|518000|0755", 0),
+            (1380759, "广东|深圳|518000|0755", 9),
+        ]);
+
+        let subset = phone_data.subset_by_province("广东").unwrap();
+        assert_eq!(subset.index_count(), 4);
+        assert_eq!(subset.find("13807580000").unwrap().card_type, "未知运营商");
+        assert!(matches!(
+            subset.find("13807590000"),
+            Err(ErrorKind::NotFound)
+        ));
+        assert!(subset.verify_integrity().is_clean());
+        assert!(subset.snapshot().records.len() < phone_data.snapshot().records.len());
+
+        assert_eq!(subset.find("13807550000").unwrap().city, "深圳");
+        assert_eq!(subset.find("13807560000").unwrap().city, "广州");
+        assert_eq!(
+            subset.find("13807570000").unwrap(),
+            phone_data.find("13807570000").unwrap()
+        );
+        assert!(matches!(
+            subset.find("13800138000"),
+            Err(ErrorKind::NotFound)
+        ));
+        assert!(matches!(
+            subset.find("13803110000"),
+            Err(ErrorKind::NotFound)
+        ));
+
+        assert!(matches!(
+            phone_data.subset_by_province("西藏"),
+            Err(ErrorKind::NotFound)
+        ));
+    }
+
+    #[test]
+    fn test_find_by_prefix_matches_find() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380755, "广东|深圳|518000|0755", 2),
+        ]);
+
+        assert_eq!(
+            phone_data.find_by_prefix(1380013).unwrap(),
+            phone_data.find("13800138000").unwrap()
+        );
+        // 按号段查询不读写结果缓存，每次都二分查找
+        let (hits, searches) = (phone_data.cache_hits(), phone_data.index_searches());
+        let cached = phone_data.get_cache_stats().size;
+        phone_data.find_by_prefix(1380013).unwrap();
+        phone_data.find_by_prefix(1380013).unwrap();
+        assert_eq!(phone_data.cache_hits(), hits);
+        assert_eq!(phone_data.index_searches(), searches + 2);
+        assert_eq!(phone_data.get_cache_stats().size, cached);
+
+        let observed = Arc::new(Mutex::new(Vec::new()));
+        let sink = observed.clone();
+        phone_data.set_query_observer(move |no, result| {
+            sink.lock().unwrap().push((no.to_string(), result.is_ok()));
+        });
+        let (queries, not_found) = (phone_data.query_count(), phone_data.not_found_count());
+        assert!(matches!(
+            phone_data.find_by_prefix(1390000),
+            Err(ErrorKind::NotFound)
+        ));
+        assert_eq!(phone_data.query_count(), queries + 1);
+        assert_eq!(phone_data.not_found_count(), not_found + 1);
+        assert_eq!(
+            *observed.lock().unwrap(),
+            vec![("1390000".to_string(), false)]
+        );
+        phone_data.clear_query_observer();
+
+        assert!(matches!(
+            phone_data.find_by_prefix(138001),
+            Err(ErrorKind::InvalidLength)
+        ));
+        assert!(matches!(
+            phone_data.find_by_prefix(13800138),
+            Err(ErrorKind::InvalidLength)
+        ));
+    }
+
+    #[test]
+    fn test_find_by_prefix_bundled_database() {
+        let phone_data = PhoneData::new().unwrap();
+        let prefixes: Vec<u32> = phone_data
+            .snapshot()
+            .index
+            .iter()
+            .step_by(997)
+            .map(|idx| idx.phone_no_prefix as u32)
+            .collect();
+        for &prefix in &prefixes {
+            let no = format!("{}1234", prefix);
+            assert_eq!(
+                phone_data.find_by_prefix(prefix).unwrap(),
+                phone_data.find(&no).unwrap(),
+                "{}",
+                no
+            );
+        }
+
+        // 只有完整号码写入缓存，按号段查询不产生缓存条目
+        let cache = phone_data.cache.read().unwrap();
+        let last = prefixes[prefixes.len() - 1].to_string();
+        assert!(!cache.contains_key(&last));
+        assert!(cache.contains_key(&format!("{}1234", last)));
+    }
+
+    #[test]
+    fn test_find_with_default_region() {
+        let bytes = encode_database("2502", &[(1380013, "北京|北京|100000|010", 1)]);
+        let default = PhoneNoInfo {
+            province: "未知".to_string(),
+            city: "未知".to_string(),
+            zip_code: String::new(),
+            area_code: String::new(),
+            card_type: "未知".to_string(),
+            icon_key: String::new(),
+            #[cfg(feature = "timezone")]
+            timezone: TIMEZONE.to_string(),
+        };
+        let phone_data = PhoneData::builder()
+            .default_region(default.clone())
+            .build_from_bytes(&bytes)
+            .unwrap();
+
+        assert_eq!(
+            phone_data.find_with_default("13900000000").unwrap(),
+            default
+        );
+        assert_eq!(
+            phone_data.find_with_default("13800138000").unwrap().city,
+            "北京"
+        );
+        assert!(matches!(
+            phone_data.find_with_default("138"),
+            Err(ErrorKind::InvalidLength)
+        ));
+
+        let without_default = PhoneData::from_bytes(&bytes).unwrap();
+        assert!(matches!(
+            without_default.find_with_default("13900000000"),
+            Err(ErrorKind::NotFound)
+        ));
+    }
+
+    #[test]
+    fn test_query_observer_tallies_outcomes() {
+        let phone_data = create_mock_phone_data_with(&[(1380013, "北京|北京|100000|010", 1)]);
+        let successes = Arc::new(AtomicU64::new(0));
+        let failures = Arc::new(AtomicU64::new(0));
+        {
+            let successes = successes.clone();
+            let failures = failures.clone();
+            phone_data.set_query_observer(move |_, result| {
+                let counter = if result.is_ok() {
+                    &successes
+                } else {
+                    &failures
+                };
+                counter.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+
+        for no in ["13800138000", "13800138000", "13900000000", "123"] {
+            let _ = phone_data.find(no);
+        }
+        assert_eq!(successes.load(Ordering::Relaxed), 2);
+        assert_eq!(failures.load(Ordering::Relaxed), 2);
+
+        phone_data.clear_query_observer();
+        let _ = phone_data.find("13800138000");
+        assert_eq!(successes.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_query_observer_can_query_again() {
+        let phone_data = Arc::new(create_mock_phone_data_with(&[(
+            1380013,
+            "北京|北京|100000|010",
+            1,
+        )]));
+        let inner = Arc::downgrade(&phone_data);
+        phone_data.set_query_observer(move |no, _| {
+            // 观察者调用时不持有缓存锁，可以写入缓存
+            if let Some(phone_data) = inner.upgrade() {
+                phone_data.clear_cache().unwrap();
+                let mut search = |no: &str, prefix| phone_data.lookup_parsed(no, prefix);
+                assert!(phone_data.lookup(no, &mut search).is_ok());
+            }
+        });
+        assert!(phone_data.find("13800138000").is_ok());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_load_gzip_database() {
+        use std::io::Write;
+
+        let raw = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/phone.dat")).unwrap();
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        encoder.write_all(&raw).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let path = std::env::temp_dir().join(format!("phone_lookup_{}.dat.gz", std::process::id()));
+        std::fs::write(&path, &compressed).unwrap();
+        let from_gz_file = PhoneData::from_file(&path.to_string_lossy());
+        std::fs::remove_file(&path).unwrap();
+        let from_gz_file = from_gz_file.unwrap();
+        let from_gz_bytes = PhoneData::from_bytes(&compressed).unwrap();
+        let from_raw = PhoneData::from_bytes(&raw).unwrap();
+
+        assert_eq!(from_gz_file.index_count(), from_raw.index_count());
+        assert_eq!(
+            from_gz_file.find("13800138000").unwrap(),
+            from_raw.find("13800138000").unwrap()
+        );
+        assert_eq!(
+            from_gz_bytes.find("18000000000").unwrap(),
+            from_raw.find("18000000000").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_trim_cache_keeps_recent_entries() {
+        let entries: Vec<(i32, &str, u8)> = (0..10)
+            .map(|i| (1380010 + i, "北京|北京|100000|010", 1))
+            .collect();
+        let phone_data = create_mock_phone_data_with(&entries);
+        for i in 0..10 {
+            phone_data.find(&format!("138001{}0000", i)).unwrap();
+        }
+        // 再次访问最早插入的两个号码，使其成为最近使用的条目
+        phone_data.find("13800100000").unwrap();
+        phone_data.find("13800110000").unwrap();
+        assert_eq!(phone_data.get_cache_stats().size, 10);
+        let full_bytes = phone_data.cache_memory_bytes();
+
+        assert_eq!(phone_data.trim_cache_to(3), 7);
+        assert_eq!(phone_data.get_cache_stats().size, 3);
+        assert_eq!(phone_data.get_cache_stats().max_size, 100);
+        assert!(phone_data.cache_memory_bytes() < full_bytes);
+
+        let cache = phone_data.cache.read().unwrap();
+        for no in ["13800190000", "13800100000", "13800110000"] {
+            assert!(cache.contains_key(no), "{} should survive", no);
+        }
+        drop(cache);
+        assert_eq!(phone_data.trim_cache_to(5), 0);
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let entries: Vec<(i32, &str, u8)> = (0..5)
+            .map(|i| (1380010 + i, "北京|北京|100000|010", 1))
+            .collect();
+        let phone_data = create_mock_phone_data_with(&entries);
+        phone_data.set_cache_size(3).unwrap();
+
+        for i in 0..3 {
+            phone_data.find(&format!("138001{}0000", i)).unwrap();
+        }
+        // 命中最早插入的号码，使 13800110000 成为最久未使用的条目
+        phone_data.find("13800100000").unwrap();
+        phone_data.find("13800130000").unwrap();
+        assert_eq!(phone_data.get_cache_stats().size, 3);
+        {
+            let cache = phone_data.cache.read().unwrap();
+            assert!(!cache.contains_key("13800110000"));
+            for no in ["13800100000", "13800120000", "13800130000"] {
+                assert!(cache.contains_key(no), "{} should survive", no);
+            }
+        }
+
+        let hits = phone_data.cache_hits();
+        phone_data.find("13800100000").unwrap();
+        assert_eq!(phone_data.cache_hits(), hits + 1);
+        phone_data.find("13800140000").unwrap();
+        assert_eq!(phone_data.get_cache_stats().size, 3);
+        assert!(!phone_data.cache.read().unwrap().contains_key("13800120000"));
+    }
+
+    #[test]
+    fn test_eviction_policy_options() {
+        assert_eq!("LFU".parse::<EvictionPolicy>(), Ok(EvictionPolicy::Lfu));
+        assert_eq!("fifo".parse::<EvictionPolicy>(), Ok(EvictionPolicy::Fifo));
+        assert!("random".parse::<EvictionPolicy>().is_err());
+
+        let phone_data =
+            PhoneData::from_file_with_options("phone.dat", true, 2, EvictionPolicy::Fifo, true)
+                .unwrap();
+        assert_eq!(phone_data.cache_max_size(), 2);
+        for no in ["13800138000", "13900139000", "13800138000", "18600186000"] {
+            phone_data.find(no).unwrap();
+        }
+        // FIFO 下命中不延长寿命，最早插入的号码先被淘汰
+        let cache = phone_data.cache.read().unwrap();
+        assert_eq!(cache.policy(), EvictionPolicy::Fifo);
+        assert!(!cache.contains_key("13800138000"));
+        assert!(cache.contains_key("13900139000"));
+    }
+
+    #[test]
+    fn test_relaxed_length_queries_by_prefix() {
+        let strict = PhoneData::new().unwrap();
+        assert!(matches!(
+            strict.find("138001380001"),
+            Err(ErrorKind::InvalidLength)
+        ));
+
+        let relaxed =
+            PhoneData::from_file_with_options("phone.dat", true, 10, EvictionPolicy::Lru, false)
+                .unwrap();
+        let expected = relaxed.find("13800138000").unwrap();
+        assert_eq!(relaxed.find("138001380001").unwrap(), expected);
+        assert_eq!(relaxed.find("1380013800012345").unwrap(), expected);
+        // 仍然要求至少7位，且前7位为数字
+        assert!(matches!(
+            relaxed.find("138001"),
+            Err(ErrorKind::InvalidLength)
+        ));
+        assert!(matches!(
+            relaxed.find("138a0138000123"),
+            Err(ErrorKind::InvalidCharacter { position: 3, .. })
+        ));
+
+        let built = PhoneData::builder()
+            .strict_length(false)
+            .build("phone.dat")
+            .unwrap();
+        assert_eq!(built.find("138001380001").unwrap(), expected);
+    }
+
+    #[test]
+    fn test_relaxed_length_applies_to_all_lookups() {
+        let relaxed = PhoneData::builder()
+            .strict_length(false)
+            .reject_implausible(true)
+            .build("phone.dat")
+            .unwrap();
+        let long = "138001380001234";
+        let expected = relaxed.find("13800138000").unwrap();
+
+        assert_eq!(relaxed.find(long).unwrap(), expected);
+        assert_eq!(
+            relaxed.find_with_segment(long).unwrap(),
+            (expected.clone(), 1380013)
+        );
+        assert_eq!(relaxed.find_card_type(long).unwrap(), CardType::Cmcc);
+        assert_eq!(relaxed.find_province(long).unwrap(), expected.province);
+        assert_eq!(relaxed.find_nearest(long).unwrap().0, 1380013);
+        assert_eq!(
+            relaxed.find_normalized("+86 138 0013 8000 1234").unwrap(),
+            expected
+        );
+        assert_eq!(relaxed.validate_number(long), NumberValidity::Valid);
+        assert!(matches!(
+            relaxed.find("238001380001234"),
+            Err(ErrorKind::NotFound)
+        ));
+
+        let strict = PhoneData::new().unwrap();
+        assert!(matches!(
+            strict.find_with_segment(long),
+            Err(ErrorKind::InvalidLength)
+        ));
+        assert!(matches!(
+            strict.find_card_type(long),
+            Err(ErrorKind::InvalidLength)
+        ));
+    }
+
+    #[test]
+    fn test_cache_ttl_requeries_expired_entry() {
+        let phone_data = PhoneData::builder()
+            .cache_ttl(Duration::from_millis(50))
+            .build("phone.dat")
+            .unwrap();
+        let info = phone_data.find("13800138000").unwrap();
+        assert_eq!(phone_data.find("13800138000").unwrap(), info);
+        assert_eq!(phone_data.index_searches(), 1);
+        assert_eq!(phone_data.cache_hits(), 1);
+
+        std::thread::sleep(Duration::from_millis(80));
+        assert_eq!(phone_data.find("13800138000").unwrap(), info);
+        assert_eq!(phone_data.index_searches(), 2);
+        assert_eq!(phone_data.cache_hits(), 1);
+        // 重新查询后刷新了条目
+        assert_eq!(phone_data.find("13800138000").unwrap(), info);
+        assert_eq!(phone_data.index_searches(), 2);
+        assert_eq!(phone_data.cache_hits(), 2);
+    }
+
+    #[test]
+    fn test_set_cache_size_resizes_in_place() {
+        let entries: Vec<(i32, &str, u8)> = (0..6)
+            .map(|i| (1380010 + i, "北京|北京|100000|010", 1))
+            .collect();
+        let phone_data = create_mock_phone_data_with(&entries);
+        for i in 0..6 {
+            phone_data.find(&format!("138001{}0000", i)).unwrap();
+        }
+
+        phone_data.set_cache_size(4).unwrap();
+        let stats = phone_data.get_cache_stats();
+        assert_eq!((stats.size, stats.max_size), (4, 4));
+        assert!(!phone_data.cache.read().unwrap().contains_key("13800110000"));
+
+        phone_data.set_cache_size(10).unwrap();
+        assert_eq!(phone_data.get_cache_stats().size, 4);
+        assert_eq!(phone_data.cache_max_size(), 10);
+        let hits = phone_data.cache_hits();
+        phone_data.find("13800150000").unwrap();
+        assert_eq!(phone_data.cache_hits(), hits + 1);
+
+        assert!(matches!(
+            phone_data.set_cache_size(0),
+            Err(ErrorKind::InvalidCacheSize)
+        ));
+        assert_eq!(phone_data.cache_max_size(), 10);
+    }
+
+    #[test]
+    fn test_region_classification() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380755, "广东|深圳|518000|0755", 1),
+            (1380991, "新疆|乌鲁木齐|830000|0991", 1),
+        ]);
+
+        let beijing = phone_data.find("13800138000").unwrap();
+        assert!(beijing.is_municipality());
+        assert!(!beijing.is_autonomous_region());
+
+        let guangdong = phone_data.find("13807550000").unwrap();
+        assert!(!guangdong.is_municipality());
+        assert!(!guangdong.is_autonomous_region());
+
+        let xinjiang = phone_data.find("13809910000").unwrap();
+        assert!(!xinjiang.is_municipality());
+        assert!(xinjiang.is_autonomous_region());
+    }
+
+    #[test]
+    fn test_find_localized() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380755, "广东|深圳|518000|0755", 8),
+        ]);
+
+        let zh = phone_data
+            .find_localized("13800138000", Locale::Zh)
+            .unwrap();
+        assert_eq!(zh, phone_data.find("13800138000").unwrap());
+
+        let en = phone_data
+            .find_localized("13800138000", Locale::En)
+            .unwrap();
+        assert_eq!(en.card_type, "China Mobile");
+        assert_eq!(en.province, "北京");
+        assert_eq!(
+            phone_data
+                .find_localized("13807550000", Locale::En)
+                .unwrap()
+                .card_type,
+            "China Broadnet MVNO"
+        );
+        assert_eq!(
+            phone_data
+                .find_localized("075512345678", Locale::En)
+                .unwrap()
+                .card_type,
+            LANDLINE_CARD_TYPE_EN
+        );
+    }
+
+    #[test]
+    fn test_find_range() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380014, "北京|北京|100000|010", 2),
+            (1380755, "广东|深圳|518000|0755", 3),
+            (1390000, "上海|上海|200000|021", 1),
+        ]);
+
+        let range = phone_data.find_range(1380000, 1389999);
+        let prefixes: Vec<u32> = range.iter().map(|(prefix, _)| *prefix).collect();
+        assert_eq!(prefixes, vec![1380013, 1380014, 1380755]);
+        assert!(range
+            .iter()
+            .all(|(prefix, _)| (1380000..=1389999).contains(prefix)));
+        assert_eq!(range[2].1.city, "深圳");
+
+        // 端点恰好是真实前缀时两端都包含
+        let prefixes: Vec<u32> = phone_data
+            .find_range(1380014, 1390000)
+            .into_iter()
+            .map(|(prefix, _)| prefix)
+            .collect();
+        assert_eq!(prefixes, vec![1380014, 1380755, 1390000]);
+
+        assert_eq!(phone_data.find_range(1380014, 1380014).len(), 1);
+        assert!(phone_data.find_range(1380100, 1380700).is_empty());
+        assert!(phone_data.find_range(1389999, 1380000).is_empty());
+        assert_eq!(phone_data.find_range(0, u32::MAX).len(), 4);
+    }
+
+    #[test]
+    fn test_page() {
+        let entries: Vec<(i32, &str, u8)> = (0..10)
+            .map(|i| (1380010 + i, "北京|北京|100000|010", 1))
+            .collect();
+        let phone_data = create_mock_phone_data_with(&entries);
+
+        let mut adjacent = phone_data.page(2, 3);
+        adjacent.extend(phone_data.page(5, 3));
+        assert_eq!(adjacent, phone_data.page(2, 6));
+        assert_eq!(adjacent[0].0, 1380012);
+        assert_eq!(adjacent.len(), 6);
+
+        assert_eq!(phone_data.page(8, 5).len(), 2);
+        assert!(phone_data.page(10, 5).is_empty());
+        assert!(phone_data.page(usize::MAX, usize::MAX).is_empty());
+    }
+
+    /// 把测试实例编码为 `phone.dat` 格式
+    pub(crate) fn encode_phone_dat(phone_data: &PhoneData, version: &str) -> Vec<u8> {
+        let mut bytes = version.as_bytes().to_vec();
+        let data = phone_data.snapshot();
+        bytes.extend_from_slice(&(data.records.len() as i32 + 8).to_le_bytes());
+        bytes.extend_from_slice(&data.records);
+        for item in data.index.iter() {
+            bytes.extend_from_slice(&item.phone_no_prefix.to_le_bytes());
+            bytes.extend_from_slice(&item.records_offset.to_le_bytes());
+            bytes.push(item.card_type);
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_from_files_patch_overrides_prefix() {
+        let base = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380755, "广东|深圳|518000|0755", 1),
+            (1390000, "上海|上海|200000|021", 2),
+        ]);
+        // 补丁的记录区更短，且新增一个位于基础库前缀之间的号段
+        let patch = create_mock_phone_data_with(&[
+            (1380755, "广东|东莞|523000|0769", 3),
+            (1385000, "浙江|杭州|310000|0571", 1),
+        ]);
+
+        let dir = std::env::temp_dir();
+        let base_path = dir.join(format!("phone_lookup_base_{}.dat", std::process::id()));
+        let patch_path = dir.join(format!("phone_lookup_patch_{}.dat", std::process::id()));
+        std::fs::write(&base_path, encode_phone_dat(&base, "2501")).unwrap();
+        std::fs::write(&patch_path, encode_phone_dat(&patch, "2502")).unwrap();
+        let merged =
+            PhoneData::from_files(&[&base_path.to_string_lossy(), &patch_path.to_string_lossy()]);
+        std::fs::remove_file(&base_path).unwrap();
+        std::fs::remove_file(&patch_path).unwrap();
+        let merged = merged.unwrap();
+
+        assert_eq!(merged.version(), "2502");
+        assert_eq!(merged.index_count(), 4);
+        let patched = merged.find("13807550000").unwrap();
+        assert_eq!(patched.city, "东莞");
+        assert_eq!(patched.card_type, "中国电信");
+        assert_eq!(merged.find("13850000000").unwrap().city, "杭州");
+        assert_eq!(merged.find("13800138000").unwrap().city, "北京");
+        assert_eq!(merged.find("13900000000").unwrap().city, "上海");
+        assert!(merged.verify_integrity().is_clean());
+
+        assert!(PhoneData::from_files(&[]).is_err());
+    }
+
+    #[test]
+    fn test_reload_while_querying() {
+        let old = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380755, "广东|深圳|518000|0755", 1),
+        ]);
+        let new = create_mock_phone_data_with(&[(1380755, "广东|东莞|523000|0769", 3)]);
+        let dir = std::env::temp_dir();
+        let old_path = dir.join(format!(
+            "phone_lookup_reload_old_{}.dat",
+            std::process::id()
+        ));
+        let new_path = dir.join(format!(
+            "phone_lookup_reload_new_{}.dat",
+            std::process::id()
+        ));
+        std::fs::write(&old_path, encode_phone_dat(&old, "2501")).unwrap();
+        std::fs::write(&new_path, encode_phone_dat(&new, "2502")).unwrap();
+        let old_path = old_path.to_string_lossy().into_owned();
+        let new_path = new_path.to_string_lossy().into_owned();
+
+        let phone_data = Arc::new(PhoneData::from_file(&old_path).unwrap());
+        assert_eq!(phone_data.find("13807550000").unwrap().city, "深圳");
+        let done = Arc::new(AtomicBool::new(false));
+
+        let reader = {
+            let phone_data = phone_data.clone();
+            let done = done.clone();
+            std::thread::spawn(move || {
+                let mut queries = 0;
+                while !done.load(Ordering::Relaxed) || queries == 0 {
+                    let city = phone_data.find("13807550000").unwrap().city;
+                    assert!(city == "深圳" || city == "东莞", "{}", city);
+                    // 旧库独有的号段在新库中可能已不存在
+                    let _ = phone_data.find("13800138000");
+                    queries += 1;
+                }
+                queries
+            })
+        };
+        for i in 0..50 {
+            let path = if i % 2 == 0 { &new_path } else { &old_path };
+            phone_data.reload(path).unwrap();
+        }
+        phone_data.reload(&new_path).unwrap();
+        done.store(true, Ordering::Relaxed);
+        assert!(reader.join().unwrap() > 0);
+
+        assert!(phone_data.reload("/nonexistent/phone.dat").is_err());
+        std::fs::remove_file(&old_path).unwrap();
+        std::fs::remove_file(&new_path).unwrap();
+
+        // 加载失败不影响当前数据，缓存中也没有旧库的结果
+        assert_eq!(phone_data.version(), "2502");
+        assert_eq!(phone_data.index_count(), 1);
+        let info = phone_data.find("13807550000").unwrap();
+        assert_eq!(info.city, "东莞");
+        assert_eq!(info.card_type, "中国电信");
+        assert!(matches!(
+            phone_data.find("13800138000"),
+            Err(ErrorKind::NotFound)
+        ));
+    }
+
+    #[test]
+    fn test_find_on_empty_database() {
+        let phone_data = create_mock_phone_data();
+        assert!(matches!(
+            phone_data.find("13800138000"),
+            Err(ErrorKind::NotFound)
+        ));
+        assert!(matches!(
+            phone_data.find_quiet("13800138000"),
+            Err(ErrorKind::NotFound)
+        ));
+        assert!(phone_data.find_batch(&["13800138000"])[0].is_err());
+    }
+
+    #[test]
+    fn test_out_of_range_record_offset() {
+        let phone_data = create_mock_phone_data_with(&[(1380013, "北京|北京|100000|010", 1)]);
+        let data = phone_data.snapshot();
+        let records_len = data.records.len();
+        for offset in [0, 7, records_len + 8, usize::MAX] {
+            assert!(matches!(
+                phone_data.parse_to_record(&data, offset),
+                Err(ErrorKind::InvalidPhoneDatabase)
+            ));
+        }
+
+        replace_index(&phone_data, |index| index[0].records_offset = 3);
+        assert!(matches!(
+            phone_data.find("13800138000"),
+            Err(ErrorKind::InvalidPhoneDatabase)
+        ));
+        replace_index(&phone_data, |index| index[0].records_offset = i32::MAX);
+        assert!(matches!(
+            phone_data.find_quiet("13800138000"),
+            Err(ErrorKind::InvalidPhoneDatabase)
+        ));
+        for offset in [3, i32::MAX] {
+            replace_index(&phone_data, |index| index[0].records_offset = offset);
+            assert!(matches!(
+                phone_data.find_province("13800138000"),
+                Err(ErrorKind::InvalidPhoneDatabase)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_truncated_records_section() {
+        // 文件头声明记录区有 100 字节，实际只有 10 字节
+        let mut bytes = b"2502".to_vec();
+        bytes.extend_from_slice(&108i32.to_le_bytes());
+        bytes.extend_from_slice(b"0123456789");
+
+        let path =
+            std::env::temp_dir().join(format!("phone_lookup_truncated_{}.dat", std::process::id()));
+        std::fs::write(&path, &bytes).unwrap();
+        let result = PhoneData::from_file(&path.to_string_lossy());
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(
+            result,
+            Err(ErrorKind::TruncatedRecords {
+                expected: 100,
+                actual: 10
+            })
+        ));
+    }
+
+    #[test]
+    fn test_index_read_errors() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380755, "广东|深圳|518000|0755", 3),
+        ]);
+        let bytes = encode_phone_dat(&phone_data, "2502");
+
+        // 最后一个索引项被截断，只保留完整的第一项
+        let truncated = PhoneData::from_bytes(&bytes[..bytes.len() - 4]).unwrap();
+        assert_eq!(truncated.index_count(), 1);
+        assert_eq!(truncated.find("13800138000").unwrap().city, "北京");
+        assert!(matches!(
+            truncated.find("13807550000"),
+            Err(ErrorKind::NotFound)
+        ));
+
+        /// 读到 `fail_at` 字节后返回非 EOF 错误的读取器
+        struct FailingReader<'a> {
+            bytes: &'a [u8],
+            read: usize,
+            fail_at: usize,
+        }
+        impl Read for FailingReader<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                if self.read >= self.fail_at {
+                    return Err(std::io::Error::other("磁盘读取失败"));
+                }
+                let end = self
+                    .fail_at
+                    .min(self.bytes.len())
+                    .min(self.read + buf.len());
+                let n = end - self.read;
+                buf[..n].copy_from_slice(&self.bytes[self.read..end]);
+                self.read = end;
+                Ok(n)
+            }
+        }
+        let reader = FailingReader {
+            bytes: &bytes,
+            read: 0,
+            fail_at: bytes.len() - 9,
+        };
+        assert!(matches!(
+            PhoneData::from_reader(reader, true, 100),
+            Err(ErrorKind::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_warm_up() {
+        let phone_data = create_mock_phone_data_with(&[
+            (1380013, "北京|北京|100000|010", 1),
+            (1380755, "广东|深圳|518000|0755", 3),
+        ]);
+        let warmed = phone_data.warm_up(&["13800138000", "13800138000", "13900000000", "12"]);
+        assert_eq!(warmed, 1);
+        assert_eq!(phone_data.get_cache_stats().size, 1);
+        assert_eq!(phone_data.get_cache_stats().total_queries, 0);
+
+        phone_data.find("13800138000").unwrap();
+        assert_eq!(phone_data.stats.cache_hits.load(Ordering::Relaxed), 1);
+
+        assert_eq!(phone_data.warm_up_all_segments(), 2);
+        assert_eq!(phone_data.get_cache_stats().size, 3);
+        phone_data.find("13807550000").unwrap();
+        assert_eq!(phone_data.stats.cache_hits.load(Ordering::Relaxed), 2);
+
+        // 缓存写满即停止
+        let many: Vec<(i32, &str, u8)> = (0..10)
+            .map(|i| (1380020 + i, "北京|北京|100000|010", 1))
+            .collect();
+        let phone_data = PhoneData {
+            cache: Arc::new(RwLock::new(cache::ResultCache::new(3))),
+            ..create_mock_phone_data_with(&many)
+        };
+        assert_eq!(phone_data.warm_up_all_segments(), 3);
+
+        let mut disabled = create_mock_phone_data_with(&many);
+        disabled.cache_enabled = false;
+        assert_eq!(disabled.warm_up(&["13800200000"]), 0);
+        assert_eq!(disabled.warm_up_all_segments(), 0);
+        assert_eq!(disabled.get_cache_stats().size, 0);
     }
 
     #[test]
@@ -611,6 +5006,9 @@ mod tests {
             zip_code: "000000".to_string(),
             area_code: "0000".to_string(),
             card_type: "测试运营商".to_string(),
+            icon_key: "test".to_string(),
+            #[cfg(feature = "timezone")]
+            timezone: TIMEZONE.to_string(),
         };
 
         // 直接向缓存中插入测试数据
@@ -622,7 +5020,7 @@ mod tests {
         // 验证缓存中的数据
         {
             let cache = phone_data.cache.read().unwrap();
-            let cached_result = cache.get(phone_number).unwrap();
+            let (cached_result, _) = cache.peek(phone_number).unwrap();
             assert_eq!(cached_result.province, "测试省");
             assert_eq!(cached_result.city, "测试市");
         }
@@ -1,30 +1,67 @@
-use actix_web::{get, middleware::Logger, post, web, App, HttpResponse, HttpServer, Responder};
+use actix_web::body::{self, BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header;
+use actix_web::middleware::{from_fn, Logger, Next};
+use actix_web::{get, post, web, App, HttpRequest, HttpResponse, HttpServer, Responder};
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
-use std::time::Instant;
-use futures::stream::{self, StreamExt};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
 
 use phone_lookup_rs::config::Config;
-use phone_lookup_rs::{PhoneData, PhoneNoInfo};
+use phone_lookup_rs::{CacheStats, GroupBy, Locale, PhoneData, PhoneNoInfo};
 
 #[derive(Clone)]
 struct AppState {
     pub phone_data: Arc<PhoneData>,
     pub config: Config,
+    /// 限制同时进行的全库扫描数量，避免占满阻塞线程池
+    pub scan_permits: Arc<Semaphore>,
 }
 
 impl AppState {
     fn new(config: Config) -> Result<Self, Box<dyn std::error::Error>> {
-        let phone_data = PhoneData::from_file_with_config(
-            &config.database.path,
-            config.cache.enabled,
-            config.cache.max_size,
-        )?;
+        let mut builder = PhoneData::builder()
+            .cache_enabled(config.cache.enabled)
+            .cache_max_size(config.cache.max_size)
+            .eviction_policy(config.cache.eviction.parse()?);
+        if let Some(ttl) = config.cache.ttl_seconds {
+            builder = builder.cache_ttl(Duration::from_secs(ttl));
+        }
+        if config.logging.slow_query_ms > 0 {
+            builder =
+                builder.slow_query_threshold(Duration::from_millis(config.logging.slow_query_ms));
+        }
+        let phone_data = builder.build(&config.database.path)?;
+        if config.cache.warm_up {
+            phone_data.warm_up_all_segments();
+        }
         Ok(AppState {
             phone_data: Arc::new(phone_data),
             config,
+            scan_permits: Arc::new(Semaphore::new(num_cpus::get().max(1))),
         })
     }
+
+    /// 在阻塞线程池中执行耗时的全库操作（扫描、导出、统计等）
+    ///
+    /// 单次查询只需微秒级，直接在 worker 线程上执行；而扫描类操作会阻塞事件循环，
+    /// 必须通过这里转移到阻塞线程池，保证同一 worker 上的普通查询不被拖慢。
+    async fn run_blocking<F, T>(&self, f: F) -> Result<T, actix_web::error::BlockingError>
+    where
+        F: FnOnce(&PhoneData) -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let _permit = self
+            .scan_permits
+            .acquire()
+            .await
+            .expect("scan semaphore closed");
+        let phone_data = self.phone_data.clone();
+        web::block(move || f(&phone_data)).await
+    }
 }
 
 /// API响应结构体
@@ -75,6 +112,22 @@ impl<T: Serialize> ApiResponse<T> {
     }
 }
 
+/// 单号查询响应，成功时附带 `Cache-Control` 以便客户端缓存
+///
+/// 数据在服务运行期间不变，缓存时长由 `server.result_cache_seconds` 控制；
+/// 失败响应不带该头，避免客户端缓存临时错误。
+fn single_query_response<T: Serialize>(response: ApiResponse<T>, config: &Config) -> HttpResponse {
+    let mut builder = HttpResponse::Ok();
+    let max_age = config.server.result_cache_seconds;
+    if response.success && max_age > 0 {
+        builder.insert_header(header::CacheControl(vec![
+            header::CacheDirective::Public,
+            header::CacheDirective::MaxAge(max_age.min(u32::MAX as u64) as u32),
+        ]));
+    }
+    builder.json(response)
+}
+
 async fn index() -> impl Responder {
     HttpResponse::Ok().json(ApiResponse::success("Phone Data API v1.0 - Ready"))
 }
@@ -82,26 +135,93 @@ async fn index() -> impl Responder {
 #[derive(Debug, Deserialize)]
 struct QueryParams {
     phone: String,
+    /// 描述文字的语言（`zh` 或 `en`），默认中文，也可写作 `lang`
+    #[serde(default, alias = "lang")]
+    locale: Locale,
+}
+
+/// 路径查询接口的可选查询参数
+#[derive(Debug, Deserialize)]
+struct LocaleParams {
+    #[serde(default, alias = "lang")]
+    locale: Locale,
+}
+
+/// 单个号码查询响应，在归属地字段之外回显查询的号码
+#[derive(Debug, Serialize)]
+struct SingleQueryResult {
+    /// 查询的手机号
+    phone: String,
+    #[serde(flatten)]
+    info: PhoneNoInfo,
+    /// 基础运营商代码（如 `CMCC`），仅英文响应返回，固定电话没有该字段
+    #[serde(skip_serializing_if = "Option::is_none")]
+    carrier_code: Option<&'static str>,
+    /// 是否为虚拟运营商，与 `carrier_code` 同时返回
+    #[serde(skip_serializing_if = "Option::is_none")]
+    is_mvno: Option<bool>,
+}
+
+impl SingleQueryResult {
+    fn new(phone: String, info: PhoneNoInfo, locale: Locale) -> Self {
+        let carrier = match locale {
+            Locale::En => info.carrier(),
+            Locale::Zh => None,
+        };
+        SingleQueryResult {
+            phone,
+            info,
+            carrier_code: carrier.map(|carrier| carrier.carrier_code()),
+            is_mvno: carrier.map(|carrier| carrier.is_virtual()),
+        }
+    }
 }
 
 /// 批量查询请求结构体
 #[derive(Debug, Deserialize)]
 struct BatchQueryRequest {
-    /// 手机号列表，最多支持100个
+    /// 手机号列表，数量上限由 `batch.max_size` 配置（默认100个）
     phones: Vec<String>,
+    /// 可选的分组维度（`province` 或 `city`），设置后响应中附带 `groups`
+    #[serde(default)]
+    group_by: Option<GroupBy>,
+    /// 为 `true` 时重复号码只查询一次，结果按原始位置展开
+    #[serde(default)]
+    dedupe: bool,
 }
 
 /// 批量查询响应结构体
 #[derive(Debug, Serialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
 struct BatchQueryResponse {
     /// 成功查询的结果
     results: Vec<PhoneQueryResult>,
     /// 查询统计信息
     stats: BatchQueryStats,
+    /// 按地区分组的手机号，失败的号码归入 `errors` 分组（仅在请求了 `group_by` 时返回）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    groups: Option<BTreeMap<String, Vec<String>>>,
+}
+
+/// 批量查询结果中失败号码所在的分组名
+const BATCH_ERRORS_GROUP: &str = "errors";
+
+/// 按指定维度把批量查询结果分组
+fn group_results(results: &[PhoneQueryResult], by: GroupBy) -> BTreeMap<String, Vec<String>> {
+    let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for result in results {
+        let key = match &result.data {
+            Some(info) => by.key(info),
+            None => BATCH_ERRORS_GROUP.to_string(),
+        };
+        groups.entry(key).or_default().push(result.phone.clone());
+    }
+    groups
 }
 
 /// 单个手机号查询结果
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
 struct PhoneQueryResult {
     /// 查询的手机号（确保与请求中的号码完全一致）
     phone: String,
@@ -117,6 +237,7 @@ struct PhoneQueryResult {
 
 /// 批量查询统计信息
 #[derive(Debug, Serialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
 struct BatchQueryStats {
     /// 查询总数
     total: usize,
@@ -140,61 +261,78 @@ async fn query_phone(info: web::Query<QueryParams>, data: web::Data<AppState>) -
 
     // 基本输入验证
     if params.phone.is_empty() || params.phone.len() < 7 {
-        let response: ApiResponse<PhoneNoInfo> = ApiResponse::error("手机号码格式无效");
+        let response: ApiResponse<SingleQueryResult> = ApiResponse::error("手机号码格式无效");
         return HttpResponse::BadRequest().json(response);
     }
 
-    let response = match data.phone_data.find(&params.phone) {
+    let logged_phone = data.config.logging.phone_for_log(&params.phone);
+    let response = match data.phone_data.find_localized(&params.phone, params.locale) {
         Ok(info) => {
-            tracing::info!("成功查询手机号: {}", params.phone);
-            ApiResponse::success(info)
+            tracing::info!("成功查询手机号: {}", logged_phone);
+            ApiResponse::success(SingleQueryResult::new(
+                params.phone.clone(),
+                info,
+                params.locale,
+            ))
         }
         Err(phone_lookup_rs::ErrorKind::NotFound) => {
-            tracing::warn!("手机号码未找到: {}", params.phone);
+            tracing::warn!("手机号码未找到: {}", logged_phone);
             ApiResponse::error_with_code(-404, "手机号码未找到")
         }
         Err(phone_lookup_rs::ErrorKind::InvalidLength) => {
-            tracing::warn!("手机号码格式无效: {}", params.phone);
+            tracing::warn!("手机号码格式无效: {}", logged_phone);
+            ApiResponse::error_with_code(-400, "手机号码格式无效")
+        }
+        Err(e @ phone_lookup_rs::ErrorKind::InvalidCharacter { .. }) => {
+            tracing::warn!("手机号码格式无效: {} - {}", logged_phone, e);
             ApiResponse::error_with_code(-400, "手机号码格式无效")
         }
         Err(phone_lookup_rs::ErrorKind::InvalidPhoneDatabase) => {
-            tracing::error!("数据库格式错误: {}", params.phone);
+            tracing::error!("数据库格式错误: {}", logged_phone);
             ApiResponse::error_with_code(-500, "数据库格式错误")
         }
         Err(phone_lookup_rs::ErrorKind::Io(e)) => {
-            tracing::error!("I/O错误: {} - {:?}", params.phone, e);
+            tracing::error!("I/O错误: {} - {:?}", logged_phone, e);
             ApiResponse::error_with_code(-500, "系统内部错误")
         }
         Err(e) => {
-            tracing::error!("查询失败: {} - {:?}", params.phone, e);
+            tracing::error!("查询失败: {} - {:?}", logged_phone, e);
             ApiResponse::error_with_code(-500, "查询失败")
         }
     };
 
-    HttpResponse::Ok().json(response)
+    single_query_response(response, &data.config)
 }
 
 #[get("/query/{phone}")]
 async fn query_phone_by_path(
     phone: web::Path<String>,
+    params: web::Query<LocaleParams>,
     data: web::Data<AppState>,
 ) -> impl Responder {
     let phone_number = phone.into_inner();
 
     // 基本输入验证
     if phone_number.is_empty() || phone_number.len() < 7 {
-        let response: ApiResponse<PhoneNoInfo> = ApiResponse::error("手机号码格式无效");
+        let response: ApiResponse<SingleQueryResult> = ApiResponse::error("手机号码格式无效");
         return HttpResponse::BadRequest().json(response);
     }
 
-    let response = match data.phone_data.find(&phone_number) {
-        Ok(info) => ApiResponse::success(info),
+    let response = match data.phone_data.find_localized(&phone_number, params.locale) {
+        Ok(info) => ApiResponse::success(SingleQueryResult::new(
+            phone_number.clone(),
+            info,
+            params.locale,
+        )),
         Err(phone_lookup_rs::ErrorKind::NotFound) => ApiResponse::error("手机号码未找到"),
-        Err(phone_lookup_rs::ErrorKind::InvalidLength) => ApiResponse::error("手机号码格式无效"),
+        Err(
+            phone_lookup_rs::ErrorKind::InvalidLength
+            | phone_lookup_rs::ErrorKind::InvalidCharacter { .. },
+        ) => ApiResponse::error("手机号码格式无效"),
         Err(_) => ApiResponse::error("查询失败"),
     };
 
-    HttpResponse::Ok().json(response)
+    single_query_response(response, &data.config)
 }
 
 #[post("/echo")]
@@ -222,20 +360,150 @@ async fn health_check(data: web::Data<AppState>) -> impl Responder {
     let health = HealthCheck {
         status: "healthy".to_string(),
         version: format!(
-            "API: {} | DB: {} | Records: {} | Cache: {} | Port: {} | Queries: {} | Cache Hit Rate: {:.2}%",
+            "API: {} | DB: {} | Records: {} | Cache: {} | Port: {} | Queries: {} | Cache Hit Rate: {:.2}% | Cache Health: {:?}",
             env!("CARGO_PKG_VERSION"),
             data.phone_data.version(),
             data.phone_data.index_count(),
             cache_status,
             data.config.server.port,
             data.phone_data.query_count(),
-            data.phone_data.cache_hit_rate()
+            data.phone_data.cache_hit_rate(),
+            data.phone_data.cache_health()
         ),
     };
     tracing::debug!("健康检查请求");
     HttpResponse::Ok().json(ApiResponse::success(health))
 }
 
+/// 以 Prometheus 文本格式渲染查询与缓存指标
+fn render_metrics(phone_data: &PhoneData) -> String {
+    use std::fmt::Write;
+
+    let cache_stats = phone_data.get_cache_stats();
+    let metrics: [(&str, &str, &str, u64); 9] = [
+        (
+            "phone_lookup_queries_total",
+            "counter",
+            "查询总数",
+            phone_data.query_count(),
+        ),
+        (
+            "phone_lookup_cache_hits_total",
+            "counter",
+            "缓存命中次数",
+            phone_data.cache_hits(),
+        ),
+        (
+            "phone_lookup_cache_misses_total",
+            "counter",
+            "缓存未命中次数",
+            phone_data.cache_misses(),
+        ),
+        (
+            "phone_lookup_not_found_total",
+            "counter",
+            "未找到的查询次数",
+            phone_data.not_found_count(),
+        ),
+        (
+            "phone_lookup_index_searches_total",
+            "counter",
+            "索引二分查找次数",
+            phone_data.index_searches(),
+        ),
+        (
+            "phone_lookup_slow_queries_total",
+            "counter",
+            "慢查询次数",
+            phone_data.slow_queries(),
+        ),
+        (
+            "phone_lookup_cache_size",
+            "gauge",
+            "当前缓存条目数",
+            cache_stats.size as u64,
+        ),
+        (
+            "phone_lookup_cache_max_size",
+            "gauge",
+            "缓存最大条目数",
+            cache_stats.max_size as u64,
+        ),
+        (
+            "phone_lookup_index_count",
+            "gauge",
+            "数据库号段数",
+            phone_data.index_count() as u64,
+        ),
+    ];
+    let mut out = String::new();
+    for (name, kind, help, value) in metrics {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} {}", name, kind);
+        let _ = writeln!(out, "{} {}", name, value);
+    }
+    out
+}
+
+#[get("/metrics")]
+async fn metrics_endpoint(data: web::Data<AppState>) -> impl Responder {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4; charset=utf-8")
+        .body(render_metrics(&data.phone_data))
+}
+
+/// 管理令牌请求头
+const ADMIN_TOKEN_HEADER: &str = "X-Admin-Token";
+
+/// 校验管理接口的访问令牌，校验失败时返回应直接发回的响应
+///
+/// 未配置 `server.admin_token` 时管理接口一律返回 403，令牌缺失或错误时返回 401。
+fn admin_rejection(req: &HttpRequest, config: &Config) -> Option<HttpResponse> {
+    let Some(expected) = config.server.admin_token.as_deref() else {
+        let response: ApiResponse<()> = ApiResponse::error_with_code(-403, "管理接口未启用");
+        return Some(HttpResponse::Forbidden().json(response));
+    };
+    let provided = req
+        .headers()
+        .get(ADMIN_TOKEN_HEADER)
+        .and_then(|value| value.to_str().ok());
+    if provided != Some(expected) {
+        let response: ApiResponse<()> = ApiResponse::error_with_code(-401, "管理令牌无效");
+        return Some(HttpResponse::Unauthorized().json(response));
+    }
+    None
+}
+
+/// 当前生效的配置（已合并配置文件与环境变量，敏感字段已隐藏）
+#[get("/config")]
+async fn effective_config(req: HttpRequest, data: web::Data<AppState>) -> impl Responder {
+    if let Some(response) = admin_rejection(&req, &data.config) {
+        return response;
+    }
+    HttpResponse::Ok().json(ApiResponse::success(data.config.redacted()))
+}
+
+/// 各省号段数量统计
+#[get("/stats/provinces")]
+async fn province_stats(data: web::Data<AppState>) -> impl Responder {
+    match data
+        .run_blocking(|phone_data| {
+            phone_data
+                .province_distribution()
+                .into_iter()
+                .collect::<BTreeMap<String, usize>>()
+        })
+        .await
+    {
+        Ok(distribution) => HttpResponse::Ok().json(ApiResponse::success(distribution)),
+        Err(e) => {
+            tracing::error!("省份统计失败: {}", e);
+            let response: ApiResponse<()> = ApiResponse::error_with_code(-500, "系统内部错误");
+            HttpResponse::InternalServerError().json(response)
+        }
+    }
+}
+
 #[post("/demo")]
 async fn demo_endpoint(pa: web::Json<ProvinceQuery>) -> impl Responder {
     let province_data = pa.into_inner();
@@ -246,6 +514,163 @@ async fn demo_endpoint(pa: web::Json<ProvinceQuery>) -> impl Responder {
     )))
 }
 
+/// 批量查询超时后未完成的号码使用的错误信息
+const BATCH_TIMEOUT_MESSAGE: &str = "查询超时";
+
+/// 设置了批量查询时限时，每查询这么多个号码检查一次是否超时
+const DEADLINE_CHECK_INTERVAL: usize = 32;
+
+/// 按小批次调用 `find_batch_map` 查询号码，到达截止时间后剩余的号码不再查询，对应结果为 `None`
+fn lookup_until(
+    phone_data: &PhoneData,
+    numbers: &[String],
+    deadline: Option<Instant>,
+) -> Vec<Option<Result<PhoneNoInfo, phone_lookup_rs::ErrorKind>>> {
+    let step = match deadline {
+        Some(_) => DEADLINE_CHECK_INTERVAL,
+        None => numbers.len().max(1),
+    };
+    let mut results = Vec::with_capacity(numbers.len());
+    for chunk in numbers.chunks(step) {
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            break;
+        }
+        let chunk: Vec<&str> = chunk.iter().map(String::as_str).collect();
+        results.extend(
+            phone_data
+                .find_batch_map(&chunk)
+                .into_iter()
+                .map(|(_, result)| Some(result)),
+        );
+    }
+    results.resize_with(numbers.len(), || None);
+    results
+}
+
+/// 把号码分成最多 `concurrency` 块，在阻塞线程池上并行查询，结果顺序与输入一一对应
+async fn lookup_concurrently(
+    phone_data: &Arc<PhoneData>,
+    numbers: Vec<String>,
+    concurrency: usize,
+    deadline: Option<Instant>,
+) -> Vec<Option<Result<PhoneNoInfo, phone_lookup_rs::ErrorKind>>> {
+    let chunk_size = numbers.len().div_ceil(concurrency.max(1)).max(1);
+    let tasks = numbers.chunks(chunk_size).map(|chunk| {
+        let phone_data = phone_data.clone();
+        let chunk = chunk.to_vec();
+        async move {
+            let len = chunk.len();
+            web::block(move || lookup_until(&phone_data, &chunk, deadline))
+                .await
+                .unwrap_or_else(|e| {
+                    tracing::error!("批量查询任务异常: {}", e);
+                    (0..len)
+                        .map(|_| Some(Err(std::io::Error::other("批量查询任务异常").into())))
+                        .collect()
+                })
+        }
+    });
+    futures::future::join_all(tasks)
+        .await
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// 把单个号码的查询结果转换为批量响应中的一项，`None` 表示超过批量查询时限未查询
+fn batch_result(
+    index: usize,
+    phone: String,
+    lookup: Option<Result<PhoneNoInfo, phone_lookup_rs::ErrorKind>>,
+) -> PhoneQueryResult {
+    match lookup {
+        None => PhoneQueryResult {
+            phone,
+            index,
+            success: false,
+            data: None,
+            error: Some(BATCH_TIMEOUT_MESSAGE.to_string()),
+        },
+        Some(Ok(info)) => PhoneQueryResult {
+            phone,
+            index,
+            success: true,
+            data: Some(info),
+            error: None,
+        },
+        Some(Err(e)) => {
+            let message = match e {
+                phone_lookup_rs::ErrorKind::NotFound => "手机号码未找到",
+                phone_lookup_rs::ErrorKind::InvalidLength
+                | phone_lookup_rs::ErrorKind::InvalidCharacter { .. } => "手机号码格式无效",
+                phone_lookup_rs::ErrorKind::InvalidPhoneDatabase => "数据库格式错误",
+                _ => "查询失败",
+            };
+            PhoneQueryResult {
+                phone,
+                index,
+                success: false,
+                data: None,
+                error: Some(message.to_string()),
+            }
+        }
+    }
+}
+
+/// 流式批量查询每次在阻塞线程池中查询并发送的号码数
+const STREAM_CHUNK_SIZE: usize = 256;
+
+/// 以 NDJSON 流式返回批量查询结果，每行一个查询结果对象
+///
+/// 号码按块在阻塞线程池中查询，每块查完立即发送，不在内存中构建完整响应。
+/// 结果按输入顺序逐行输出，不附带统计信息，也不支持 `group_by` 和 `dedupe`；
+/// 数量上限和超时与 `POST /batch-query` 相同。
+#[post("/batch-query/stream")]
+async fn batch_query_stream(
+    request: web::Json<BatchQueryRequest>,
+    data: web::Data<AppState>,
+) -> HttpResponse {
+    let phones = request.into_inner().phones;
+    if phones.is_empty() {
+        let response: ApiResponse<BatchQueryResponse> = ApiResponse::error("手机号列表不能为空");
+        return HttpResponse::BadRequest().json(response);
+    }
+    if phones.len() > data.config.batch.max_size {
+        let response: ApiResponse<BatchQueryResponse> =
+            ApiResponse::error("批量查询的手机号数量超过上限");
+        return HttpResponse::BadRequest().json(response);
+    }
+
+    tracing::info!("开始流式批量查询 {} 个手机号", phones.len());
+    let deadline = (data.config.batch.timeout_ms > 0)
+        .then(|| Instant::now() + Duration::from_millis(data.config.batch.timeout_ms));
+    let chunks: Vec<(usize, Vec<String>)> = phones
+        .chunks(STREAM_CHUNK_SIZE)
+        .enumerate()
+        .map(|(i, chunk)| (i * STREAM_CHUNK_SIZE, chunk.to_vec()))
+        .collect();
+    let phone_data = data.phone_data.clone();
+    let body = futures::stream::iter(chunks).then(move |(start, chunk)| {
+        let phone_data = phone_data.clone();
+        async move {
+            let (chunk, lookups) = web::block(move || {
+                let lookups = lookup_until(&phone_data, &chunk, deadline);
+                (chunk, lookups)
+            })
+            .await?;
+            let mut lines = Vec::new();
+            for (i, (phone, lookup)) in chunk.into_iter().zip(lookups).enumerate() {
+                serde_json::to_writer(&mut lines, &batch_result(start + i, phone, lookup))?;
+                lines.push(b'\n');
+            }
+            Ok::<_, actix_web::Error>(web::Bytes::from(lines))
+        }
+    });
+    HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .streaming(body)
+}
+
 /// 批量查询手机号归属地信息
 /// 
 /// 支持同时查询多个手机号，返回每个手机号的查询结果和统计信息
@@ -254,8 +679,68 @@ async fn batch_query(
     request: web::Json<BatchQueryRequest>, 
     data: web::Data<AppState>
 ) -> impl Responder {
+    run_batch_query(
+        request.into_inner(),
+        &data,
+        data.config.batch.max_size,
+        false,
+    )
+    .await
+}
+
+/// `GET /batch` 单次最多查询的号码数，避免 URL 过长被代理或 CDN 截断
+const BATCH_GET_MAX_PHONES: usize = 50;
+
+/// `GET /batch` 查询参数
+#[derive(Debug, Deserialize)]
+struct BatchGetParams {
+    /// 逗号分隔的手机号列表
+    phones: String,
+    #[serde(default)]
+    group_by: Option<GroupBy>,
+    #[serde(default)]
+    dedupe: bool,
+}
+
+/// 解析逗号分隔的号码列表，忽略两侧空白和空项
+fn split_phones(phones: &str) -> Vec<String> {
+    phones
+        .split(',')
+        .map(str::trim)
+        .filter(|phone| !phone.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// 以 GET 方式批量查询，如 `/batch?phones=13800138000,18086834111`
+///
+/// 与 `POST /batch-query` 结果相同，号码数还受 [`BATCH_GET_MAX_PHONES`] 限制；
+/// 有成功结果时附带 `Cache-Control`，便于浏览器和 CDN 缓存。
+#[get("/batch")]
+async fn batch_query_get(
+    params: web::Query<BatchGetParams>,
+    data: web::Data<AppState>,
+) -> impl Responder {
+    let params = params.into_inner();
+    let batch_request = BatchQueryRequest {
+        phones: split_phones(&params.phones),
+        group_by: params.group_by,
+        dedupe: params.dedupe,
+    };
+    let max_size = data.config.batch.max_size.min(BATCH_GET_MAX_PHONES);
+    run_batch_query(batch_request, &data, max_size, true).await
+}
+
+/// 批量查询的公共流程，`max_size` 为本次请求允许的号码数上限
+///
+/// `cacheable` 为 `true` 且至少一个号码查询成功时附带与单号查询相同的 `Cache-Control`。
+async fn run_batch_query(
+    batch_request: BatchQueryRequest,
+    data: &AppState,
+    max_size: usize,
+    cacheable: bool,
+) -> HttpResponse {
     let start_time = Instant::now();
-    let batch_request = request.into_inner();
     
     // 输入验证
     if batch_request.phones.is_empty() {
@@ -263,72 +748,61 @@ async fn batch_query(
         return HttpResponse::BadRequest().json(response);
     }
     
-    if batch_request.phones.len() > 100 {
-        let response: ApiResponse<BatchQueryResponse> = ApiResponse::error("批量查询最多支持100个手机号");
+    if batch_request.phones.len() > max_size {
+        let response: ApiResponse<BatchQueryResponse> = ApiResponse::error("批量查询的手机号数量超过上限");
         return HttpResponse::BadRequest().json(response);
     }
     
-    // 检查每个手机号的基本格式
-    for phone in &batch_request.phones {
-        if phone.is_empty() || phone.len() < 7 || phone.len() > 11 {
-            let response: ApiResponse<BatchQueryResponse> = ApiResponse::error("手机号格式无效");
-            return HttpResponse::BadRequest().json(response);
-        }
-    }
+    // 号码格式由查询本身校验，格式无效的号码作为失败项返回，
+    // 这样固定电话和 strict_length = false 时的长号码与单号查询行为一致
     
     tracing::info!("开始批量查询 {} 个手机号", batch_request.phones.len());
     
-    // 使用 futures::stream 进行优化的并发查询，自动保证结果顺序
-    let phone_data = data.phone_data.clone();
-    let phones = batch_request.phones.clone();
-    
-    // 创建查询结果的 Future 流（带索引以确保明确映射）
-    let results_stream = stream::iter(phones.into_iter().enumerate()).map(|(index, phone)| {
-        let phone_data = phone_data.clone();
-        async move {
-            let phone_clone = phone.clone();
-            match phone_data.find(&phone) {
-                Ok(info) => PhoneQueryResult {
-                    phone: phone_clone,
-                    index,
-                    success: true,
-                    data: Some(info),
-                    error: None,
-                },
-                Err(phone_lookup_rs::ErrorKind::NotFound) => PhoneQueryResult {
-                    phone: phone_clone,
-                    index,
-                    success: false,
-                    data: None,
-                    error: Some("手机号码未找到".to_string()),
-                },
-                Err(phone_lookup_rs::ErrorKind::InvalidLength) => PhoneQueryResult {
-                    phone: phone_clone,
-                    index,
-                    success: false,
-                    data: None,
-                    error: Some("手机号码格式无效".to_string()),
-                },
-                Err(phone_lookup_rs::ErrorKind::InvalidPhoneDatabase) => PhoneQueryResult {
-                    phone: phone_clone,
-                    index,
-                    success: false,
-                    data: None,
-                    error: Some("数据库格式错误".to_string()),
-                },
-                Err(_) => PhoneQueryResult {
-                    phone: phone_clone,
-                    index,
-                    success: false,
-                    data: None,
-                    error: Some("查询失败".to_string()),
-                }
-            }
-        }
-    });
+    let phones: Vec<(usize, String)> = if batch_request.dedupe {
+        let mut seen = HashSet::new();
+        batch_request
+            .phones
+            .iter()
+            .cloned()
+            .enumerate()
+            .filter(|(_, phone)| seen.insert(phone.clone()))
+            .collect()
+    } else {
+        batch_request.phones.iter().cloned().enumerate().collect()
+    };
     
-    // 并发执行查询并收集结果（保持原始顺序）
-    let results: Vec<PhoneQueryResult> = results_stream.buffered(100).collect().await;
+    // 库层批量查询保证结果与输入顺序一一对应
+    let numbers: Vec<String> = phones.iter().map(|(_, phone)| phone.clone()).collect();
+    let deadline = (data.config.batch.timeout_ms > 0)
+        .then(|| start_time + Duration::from_millis(data.config.batch.timeout_ms));
+    let lookups = lookup_concurrently(
+        &data.phone_data,
+        numbers,
+        data.config.batch.concurrency,
+        deadline,
+    )
+    .await;
+    let mut results: Vec<PhoneQueryResult> = phones
+        .into_iter()
+        .zip(lookups)
+        .map(|((index, phone), lookup)| batch_result(index, phone, lookup))
+        .collect();
+
+    // 去重模式下把每个不同号码的结果展开回所有原始位置
+    if batch_request.dedupe {
+        let by_phone: HashMap<&str, &PhoneQueryResult> =
+            results.iter().map(|r| (r.phone.as_str(), r)).collect();
+        let expanded = batch_request
+            .phones
+            .iter()
+            .enumerate()
+            .map(|(index, phone)| PhoneQueryResult {
+                index,
+                ..by_phone[phone.as_str()].clone()
+            })
+            .collect();
+        results = expanded;
+    }
     
     // 统计查询结果
     let total = results.len();
@@ -343,9 +817,14 @@ async fn batch_query(
         processing_time_ms: processing_time,
     };
     
+    let groups = batch_request
+        .group_by
+        .map(|by| group_results(&results, by));
+
     let batch_response = BatchQueryResponse {
         results,
         stats,
+        groups,
     };
     
     tracing::info!(
@@ -353,16 +832,190 @@ async fn batch_query(
         total, success_count, failed_count, processing_time
     );
     
-    HttpResponse::Ok().json(ApiResponse::success(batch_response))
+    // 全部失败时按配置返回状态码，部分成功始终为 200
+    let status = if success_count == 0 {
+        actix_web::http::StatusCode::from_u16(data.config.server.batch_fail_status)
+            .unwrap_or(actix_web::http::StatusCode::OK)
+    } else {
+        actix_web::http::StatusCode::OK
+    };
+    let mut builder = HttpResponse::build(status);
+    let max_age = data.config.server.result_cache_seconds;
+    if cacheable && success_count > 0 && max_age > 0 {
+        builder.insert_header(header::CacheControl(vec![
+            header::CacheDirective::Public,
+            header::CacheDirective::MaxAge(max_age.min(u32::MAX as u64) as u32),
+        ]));
+    }
+    builder.json(ApiResponse::success(batch_response))
+}
+
+/// 启动阶段耗时统计，每次 `record` 记录距上一个阶段结束的耗时
+struct StartupTimings {
+    started: Instant,
+    last: Instant,
+    phases: Vec<(&'static str, Duration)>,
+}
+
+impl StartupTimings {
+    fn start() -> Self {
+        let now = Instant::now();
+        StartupTimings {
+            started: now,
+            last: now,
+            phases: Vec::new(),
+        }
+    }
+
+    /// 结束当前阶段并记录其耗时
+    fn record(&mut self, phase: &'static str) {
+        let now = Instant::now();
+        self.phases.push((phase, now - self.last));
+        self.last = now;
+    }
+
+    fn total(&self) -> Duration {
+        self.last - self.started
+    }
+}
+
+impl std::fmt::Display for StartupTimings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, (phase, duration)) in self.phases.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}={}ms", phase, duration.as_millis())?;
+        }
+        Ok(())
+    }
+}
+
+/// 服务停止时输出的本次运行统计，供容量规划参考
+struct ShutdownStats {
+    total_queries: u64,
+    cache_hit_rate: f64,
+    cache: CacheStats,
+}
+
+impl ShutdownStats {
+    fn collect(phone_data: &PhoneData) -> Self {
+        ShutdownStats {
+            total_queries: phone_data.query_count(),
+            cache_hit_rate: phone_data.cache_hit_rate(),
+            cache: phone_data.get_cache_stats(),
+        }
+    }
+}
+
+impl std::fmt::Display for ShutdownStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "查询总数={} 缓存命中率={:.2}% 缓存条目={}/{} 命中={} 未命中={} 未找到={}",
+            self.total_queries,
+            self.cache_hit_rate,
+            self.cache.size,
+            self.cache.max_size,
+            self.cache.hits,
+            self.cache.misses,
+            self.cache.not_found
+        )
+    }
+}
+
+/// 等待 Ctrl-C 或（Unix 上的）SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("监听 Ctrl-C 信号失败: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("监听 SIGTERM 信号失败: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+/// 请求是否带有 `pretty=true`（或 `pretty=1`）查询参数
+fn wants_pretty(query: &str) -> bool {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .any(|(key, value)| key == "pretty" && (value == "true" || value == "1"))
+}
+
+/// 按需把 JSON 响应重新格式化为缩进输出，方便用 curl 调试
+///
+/// 未携带 `pretty` 参数的请求直接透传，不产生额外开销。
+async fn pretty_json(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, actix_web::Error> {
+    let pretty = wants_pretty(req.query_string());
+    let res = next.call(req).await?.map_into_boxed_body();
+    let is_json = res
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    if !pretty || !is_json {
+        return Ok(res);
+    }
+
+    let (req, res) = res.into_parts();
+    let (res, body) = res.into_parts();
+    let bytes = body::to_bytes(body).await.unwrap_or_default();
+    let formatted = serde_json::from_slice::<serde_json::Value>(&bytes)
+        .and_then(|value| serde_json::to_string_pretty(&value))
+        .map(String::into_bytes)
+        .unwrap_or_else(|_| bytes.to_vec());
+    let res = res.set_body(BoxBody::new(formatted));
+    Ok(ServiceResponse::new(req, res))
+}
+
+/// 注册所有 HTTP 路由
+fn configure_services(cfg: &mut web::ServiceConfig) {
+    cfg.service(query_phone)
+        .service(query_phone_by_path)
+        .service(batch_query)
+        .service(batch_query_get)
+        .service(batch_query_stream)
+        .service(health_check)
+        .service(metrics_endpoint)
+        .service(province_stats)
+        .service(effective_config)
+        .service(demo_endpoint)
+        .service(echo)
+        .route("/", web::get().to(index));
 }
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
+    let mut timings = StartupTimings::start();
+
     // 加载配置
     let config = Config::load().unwrap_or_else(|e| {
         eprintln!("Failed to load config: {}", e);
         std::process::exit(1);
     });
+    timings.record("config");
 
     // 初始化日志系统
     let log_level = match config.logging.level.as_str() {
@@ -390,8 +1043,13 @@ async fn main() -> std::io::Result<()> {
         tracing::error!("Failed to initialize app state: {}", e);
         std::process::exit(1);
     });
+    let phone_data = app_state.phone_data.clone();
+    timings.record("database");
 
-    let bind_address = (config.server.host.clone(), config.server.port);
+    let bind_addresses = config.server.bind_addresses().unwrap_or_else(|e| {
+        tracing::error!("Failed to resolve bind addresses: {}", e);
+        std::process::exit(1);
+    });
     let workers = if config.server.workers == 0 {
         num_cpus::get()
     } else {
@@ -399,26 +1057,563 @@ async fn main() -> std::io::Result<()> {
     };
 
     tracing::info!(
-        "启动手机号归属地查询 API 服务器: {}:{} (workers: {})",
-        config.server.host,
-        config.server.port,
+        "启动手机号归属地查询 API 服务器: {:?} (workers: {})",
+        bind_addresses,
         workers
     );
 
-    HttpServer::new(move || {
+    let mut server = HttpServer::new(move || {
         App::new()
             .app_data(web::Data::new(app_state.clone()))
+            .wrap(from_fn(pretty_json))
             .wrap(Logger::default())
-            .service(query_phone)
-            .service(query_phone_by_path)
-            .service(batch_query)
-            .service(health_check)
-            .service(demo_endpoint)
-            .service(echo)
-            .route("/", web::get().to(index))
+            .configure(configure_services)
     })
     .workers(workers)
-    .bind(bind_address)?
-    .run()
-    .await
+    .disable_signals();
+    for addr in bind_addresses {
+        server = server.bind(addr)?;
+    }
+    timings.record("bind");
+
+    tracing::info!(
+        total_ms = timings.total().as_millis() as u64,
+        phases = %timings,
+        "启动耗时统计"
+    );
+
+    // 由自己的信号任务停止服务，等进行中的请求处理完后再输出统计
+    let server = server.run();
+    let handle = server.handle();
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        tracing::info!("收到退出信号，正在停止服务");
+        handle.stop(true).await;
+    });
+    server.await?;
+
+    let stats = ShutdownStats::collect(&phone_data);
+    tracing::info!(
+        total_queries = stats.total_queries,
+        cache_hit_rate = stats.cache_hit_rate,
+        "服务已停止，本次运行统计: {}",
+        stats
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test as actix_test;
+
+    /// 批量统计中成功数的字段名，启用 `camel-case` 特性时为驼峰命名
+    const SUCCESS_COUNT_KEY: &str = if cfg!(feature = "camel-case") {
+        "successCount"
+    } else {
+        "success_count"
+    };
+
+    fn test_state() -> AppState {
+        AppState::new(Config::default()).expect("Failed to load phone data")
+    }
+
+    macro_rules! init_app {
+        ($state:expr) => {
+            actix_test::init_service(
+                App::new()
+                    .app_data(web::Data::new($state))
+                    .wrap(from_fn(pretty_json))
+                    .configure(configure_services),
+            )
+            .await
+        };
+    }
+
+    #[actix_web::test]
+    async fn test_scan_does_not_starve_fast_queries() {
+        let app = init_app!(test_state());
+
+        let scans = futures::future::join_all((0..4).map(|_| async {
+            let req = actix_test::TestRequest::get().uri("/stats/provinces").to_request();
+            let resp = actix_test::call_service(&app, req).await;
+            assert!(resp.status().is_success());
+            Instant::now()
+        }));
+        let queries = async {
+            for _ in 0..20 {
+                let req = actix_test::TestRequest::get()
+                    .uri("/query?phone=13800138000")
+                    .to_request();
+                let resp = actix_test::call_service(&app, req).await;
+                assert!(resp.status().is_success());
+            }
+            Instant::now()
+        };
+
+        let (scans_done, queries_done) = futures::join!(scans, queries);
+        // 扫描在阻塞线程池中进行，普通查询应在任何一个扫描完成之前全部结束
+        assert!(scans_done.iter().all(|done| queries_done <= *done));
+    }
+
+    #[actix_web::test]
+    async fn test_pretty_json_output() {
+        let app = init_app!(test_state());
+
+        let req = actix_test::TestRequest::get()
+            .uri("/query?phone=13800138000&pretty=true")
+            .to_request();
+        let body = actix_test::call_and_read_body(&app, req).await;
+        let text = std::str::from_utf8(&body).unwrap();
+        assert!(text.contains("\n  \"code\": 0"));
+        assert!(text.find("\"code\"") < text.find("\"data\""));
+
+        let req = actix_test::TestRequest::get()
+            .uri("/query?phone=13800138000")
+            .to_request();
+        let body = actix_test::call_and_read_body(&app, req).await;
+        assert!(!body.contains(&b'\n'));
+    }
+
+    #[actix_web::test]
+    async fn test_batch_query_dedupe() {
+        let state = test_state();
+        let phone_data = state.phone_data.clone();
+        let app = init_app!(state);
+        let phones = ["13800138000", "13900000000", "13800138000", "13800138000"];
+
+        for (dedupe, expected_queries) in [(true, 2), (false, 4)] {
+            let before = phone_data.query_count();
+            let req = actix_test::TestRequest::post()
+                .uri("/batch-query")
+                .set_json(serde_json::json!({ "phones": phones, "dedupe": dedupe }))
+                .to_request();
+            let body: serde_json::Value = actix_test::call_and_read_body_json(&app, req).await;
+            assert_eq!(phone_data.query_count() - before, expected_queries);
+
+            let results = body["data"]["results"].as_array().unwrap();
+            assert_eq!(results.len(), phones.len());
+            for (index, result) in results.iter().enumerate() {
+                assert_eq!(result["index"], index);
+                assert_eq!(result["phone"], phones[index]);
+            }
+            assert_eq!(results[3]["data"]["province"], "北京");
+            assert_eq!(body["data"]["stats"]["total"], 4);
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_effective_config_endpoint() {
+        let mut state = test_state();
+        state.config.server.port = 9090;
+        state.config.server.admin_token = Some("secret".to_string());
+        let app = init_app!(state);
+
+        let req = actix_test::TestRequest::get()
+            .uri("/config")
+            .insert_header((ADMIN_TOKEN_HEADER, "secret"))
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let body: serde_json::Value = actix_test::read_body_json(resp).await;
+        assert_eq!(body["data"]["server"]["port"], 9090);
+        assert_eq!(
+            body["data"]["server"]["admin_token"],
+            phone_lookup_rs::config::REDACTED
+        );
+
+        for token in [None, Some("wrong")] {
+            let mut req = actix_test::TestRequest::get().uri("/config");
+            if let Some(token) = token {
+                req = req.insert_header((ADMIN_TOKEN_HEADER, token));
+            }
+            let resp = actix_test::call_service(&app, req.to_request()).await;
+            assert_eq!(resp.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_config_endpoint_disabled_without_token() {
+        let app = init_app!(test_state());
+        let req = actix_test::TestRequest::get()
+            .uri("/config")
+            .insert_header((ADMIN_TOKEN_HEADER, "anything"))
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), actix_web::http::StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    async fn test_single_query_echoes_phone() {
+        let app = init_app!(test_state());
+        for uri in ["/query?phone=13800138000", "/query/13800138000"] {
+            let req = actix_test::TestRequest::get().uri(uri).to_request();
+            let body: serde_json::Value = actix_test::call_and_read_body_json(&app, req).await;
+            assert_eq!(body["data"]["phone"], "13800138000");
+            assert_eq!(body["data"]["province"], "北京");
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_query_with_english_locale() {
+        let app = init_app!(test_state());
+        for uri in [
+            "/query?phone=13800138000&locale=en",
+            "/query/13800138000?locale=en",
+        ] {
+            let req = actix_test::TestRequest::get().uri(uri).to_request();
+            let body: serde_json::Value = actix_test::call_and_read_body_json(&app, req).await;
+            assert_eq!(body["data"]["card_type"], "China Mobile");
+        }
+
+        let req = actix_test::TestRequest::get()
+            .uri("/query?phone=13800138000")
+            .to_request();
+        let body: serde_json::Value = actix_test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["data"]["card_type"], "中国移动");
+        assert!(body["data"].get("carrier_code").is_none());
+        assert!(body["data"].get("is_mvno").is_none());
+    }
+
+    #[actix_web::test]
+    async fn test_query_lang_en_structured_carrier() {
+        let app = init_app!(test_state());
+        for uri in [
+            "/query?phone=13800138000&lang=en",
+            "/query/13800138000?lang=en",
+        ] {
+            let req = actix_test::TestRequest::get().uri(uri).to_request();
+            let body: serde_json::Value = actix_test::call_and_read_body_json(&app, req).await;
+            assert_eq!(body["data"]["card_type"], "China Mobile");
+            assert_eq!(body["data"]["carrier_code"], "CMCC");
+            assert_eq!(body["data"]["is_mvno"], false);
+        }
+
+        // 1349 号段为中国移动虚拟运营商
+        let req = actix_test::TestRequest::get()
+            .uri("/query/13490000000?lang=en")
+            .to_request();
+        let body: serde_json::Value = actix_test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["data"]["carrier_code"], "CMCC");
+        assert_eq!(body["data"]["is_mvno"], true);
+
+        let req = actix_test::TestRequest::get()
+            .uri("/query?phone=13800138000&lang=zh")
+            .to_request();
+        let body: serde_json::Value = actix_test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["data"]["card_type"], "中国移动");
+        assert!(body["data"].get("carrier_code").is_none());
+    }
+
+    #[actix_web::test]
+    async fn test_batch_all_failed_status() {
+        use actix_web::http::StatusCode;
+
+        let mut state = test_state();
+        state.config.server.batch_fail_status = 422;
+        let app = init_app!(state);
+
+        let post = |phones: &[&str]| {
+            actix_test::TestRequest::post()
+                .uri("/batch-query")
+                .set_json(serde_json::json!({ "phones": phones }))
+                .to_request()
+        };
+        let resp = actix_test::call_service(&app, post(&["abcdefg", "10000000000"])).await;
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body: serde_json::Value = actix_test::read_body_json(resp).await;
+        assert_eq!(body["data"]["stats"][SUCCESS_COUNT_KEY], 0);
+
+        let resp = actix_test::call_service(&app, post(&["abcdefg", "13800138000"])).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        // 默认配置下全部失败仍返回 200
+        let app = init_app!(test_state());
+        let resp = actix_test::call_service(&app, post(&["abcdefg"])).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn test_batch_query_landline() {
+        let app = init_app!(test_state());
+        let req = actix_test::TestRequest::post()
+            .uri("/batch-query")
+            .set_json(serde_json::json!({ "phones": ["075512345678", "13800138000", "123"] }))
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let body: serde_json::Value = actix_test::read_body_json(resp).await;
+        let results = body["data"]["results"].as_array().unwrap();
+        assert_eq!(results[0]["success"], true);
+        assert_eq!(results[0]["data"]["city"], "深圳");
+        assert_eq!(results[1]["success"], true);
+        assert_eq!(results[2]["success"], false);
+        assert_eq!(results[2]["error"], "手机号码格式无效");
+    }
+
+    #[actix_web::test]
+    async fn test_batch_query_relaxed_length() {
+        let mut state = test_state();
+        state.phone_data = Arc::new(
+            PhoneData::builder()
+                .strict_length(false)
+                .build(&state.config.database.path)
+                .unwrap(),
+        );
+        let app = init_app!(state);
+
+        let req = actix_test::TestRequest::post()
+            .uri("/batch-query")
+            .set_json(serde_json::json!({ "phones": ["138001380001234"] }))
+            .to_request();
+        let body: serde_json::Value = actix_test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["data"]["results"][0]["success"], true);
+        assert_eq!(body["data"]["results"][0]["data"]["province"], "北京");
+
+        let req = actix_test::TestRequest::get()
+            .uri("/batch?phones=138001380001234,13800138000")
+            .to_request();
+        let body: serde_json::Value = actix_test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["data"]["results"][0]["success"], true);
+        assert_eq!(body["data"]["results"][1]["success"], true);
+    }
+
+    #[actix_web::test]
+    async fn test_batch_max_size_and_concurrency() {
+        use actix_web::http::StatusCode;
+
+        let mut state = test_state();
+        state.config.batch.max_size = 3;
+        state.config.batch.concurrency = 2;
+        let app = init_app!(state);
+
+        let post = |phones: &[&str]| {
+            actix_test::TestRequest::post()
+                .uri("/batch-query")
+                .set_json(serde_json::json!({ "phones": phones }))
+                .to_request()
+        };
+        let resp = actix_test::call_service(&app, post(&["13800138000"; 4])).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = actix_test::read_body_json(resp).await;
+        assert_eq!(body["message"], "批量查询的手机号数量超过上限");
+
+        let resp =
+            actix_test::call_service(&app, post(&["13800138000", "abcdefg", "18086834111"])).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: serde_json::Value = actix_test::read_body_json(resp).await;
+        let results = body["data"]["results"].as_array().unwrap();
+        let phones: Vec<_> = results.iter().map(|r| r["phone"].clone()).collect();
+        assert_eq!(phones, ["13800138000", "abcdefg", "18086834111"]);
+        assert_eq!(body["data"]["stats"][SUCCESS_COUNT_KEY], 2);
+    }
+
+    #[actix_web::test]
+    async fn test_batch_get_comma_separated() {
+        use actix_web::http::StatusCode;
+
+        let app = init_app!(test_state());
+        let get = |query: &str| {
+            actix_test::TestRequest::get()
+                .uri(&format!("/batch?{}", query))
+                .to_request()
+        };
+
+        let resp = actix_test::call_service(
+            &app,
+            get("phones=13800138000,%20abcdefg,,18086834111&group_by=province"),
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(resp.headers().contains_key(header::CACHE_CONTROL));
+        let body: serde_json::Value = actix_test::read_body_json(resp).await;
+        let results = body["data"]["results"].as_array().unwrap();
+        let phones: Vec<_> = results.iter().map(|r| r["phone"].clone()).collect();
+        assert_eq!(phones, ["13800138000", "abcdefg", "18086834111"]);
+        assert_eq!(body["data"]["stats"][SUCCESS_COUNT_KEY], 2);
+        assert!(body["data"]["groups"]["errors"].is_array());
+
+        let too_many = vec!["13800138000"; BATCH_GET_MAX_PHONES + 1].join(",");
+        let resp = actix_test::call_service(&app, get(&format!("phones={}", too_many))).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = actix_test::read_body_json(resp).await;
+        assert_eq!(body["message"], "批量查询的手机号数量超过上限");
+
+        let resp = actix_test::call_service(&app, get("phones=,")).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn test_batch_query_stream_ndjson() {
+        use actix_web::http::StatusCode;
+
+        let mut state = test_state();
+        state.config.batch.max_size = 1000;
+        let app = init_app!(state);
+
+        // 超过一个块，验证跨块的行数和索引
+        let phones: Vec<&str> = (0..STREAM_CHUNK_SIZE + 44)
+            .map(|i| if i % 3 == 0 { "abcdefg" } else { "13800138000" })
+            .collect();
+        let req = actix_test::TestRequest::post()
+            .uri("/batch-query/stream")
+            .set_json(serde_json::json!({ "phones": phones }))
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/x-ndjson"
+        );
+
+        let body = actix_test::read_body(resp).await;
+        let lines: Vec<serde_json::Value> = std::str::from_utf8(&body)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), phones.len());
+        for (i, line) in lines.iter().enumerate() {
+            assert_eq!(line["index"], i);
+            assert_eq!(line["phone"], phones[i]);
+            if i % 3 == 0 {
+                assert_eq!(line["success"], false);
+                assert_eq!(line["error"], "手机号码格式无效");
+            } else {
+                assert_eq!(line["success"], true);
+                assert_eq!(line["data"]["province"], "北京");
+            }
+        }
+
+        let req = actix_test::TestRequest::post()
+            .uri("/batch-query/stream")
+            .set_json(serde_json::json!({ "phones": [] }))
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_shutdown_stats() {
+        let state = test_state();
+        let phone_data = state.phone_data.clone();
+        let before = ShutdownStats::collect(&phone_data);
+
+        phone_data.find("13800138000").unwrap();
+        phone_data.find("13800138000").unwrap();
+        let stats = ShutdownStats::collect(&state.phone_data);
+        assert_eq!(stats.total_queries, before.total_queries + 2);
+        assert_eq!(stats.cache.hits, before.cache.hits + 1);
+        assert_eq!(stats.cache.total_queries, stats.total_queries);
+        assert!(stats.cache_hit_rate > 0.0);
+
+        let line = stats.to_string();
+        assert!(line.starts_with(&format!("查询总数={} ", stats.total_queries)));
+        assert!(line.contains(&format!("缓存命中率={:.2}%", stats.cache_hit_rate)));
+    }
+
+    #[test]
+    fn test_lookup_until_deadline() {
+        let state = test_state();
+        let numbers = vec!["13800138000".to_string(), "18086834111".to_string()];
+
+        let results = lookup_until(&state.phone_data, &numbers, None);
+        assert!(results.iter().all(|r| matches!(r, Some(Ok(_)))));
+
+        // 截止时间已过，所有号码都标记为超时
+        let results = lookup_until(&state.phone_data, &numbers, Some(Instant::now()));
+        assert!(results.iter().all(Option::is_none));
+    }
+
+    #[actix_web::test]
+    async fn test_single_query_cache_control() {
+        let mut state = test_state();
+        state.config.server.result_cache_seconds = 120;
+        let app = init_app!(state);
+
+        for uri in ["/query?phone=13800138000", "/query/13800138000"] {
+            let req = actix_test::TestRequest::get().uri(uri).to_request();
+            let resp = actix_test::call_service(&app, req).await;
+            let value = resp.headers().get(header::CACHE_CONTROL).unwrap();
+            assert_eq!(value.to_str().unwrap(), "public, max-age=120");
+        }
+
+        let req = actix_test::TestRequest::get()
+            .uri("/query?phone=10000000000")
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert!(resp.headers().get(header::CACHE_CONTROL).is_none());
+
+        let mut state = test_state();
+        state.config.server.result_cache_seconds = 0;
+        let app = init_app!(state);
+        let req = actix_test::TestRequest::get()
+            .uri("/query?phone=13800138000")
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert!(resp.headers().get(header::CACHE_CONTROL).is_none());
+    }
+
+    #[actix_web::test]
+    async fn test_metrics_endpoint() {
+        let app = init_app!(test_state());
+        for _ in 0..2 {
+            let req = actix_test::TestRequest::get()
+                .uri("/query?phone=13800138000")
+                .to_request();
+            actix_test::call_service(&app, req).await;
+        }
+
+        let req = actix_test::TestRequest::get().uri("/metrics").to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let content_type = resp.headers().get(header::CONTENT_TYPE).unwrap();
+        assert!(content_type.to_str().unwrap().starts_with("text/plain"));
+        let body = actix_test::read_body(resp).await;
+        let text = std::str::from_utf8(&body).unwrap();
+        let value = |name: &str| -> u64 {
+            text.lines()
+                .find_map(|line| line.strip_prefix(&format!("{} ", name)))
+                .unwrap_or_else(|| panic!("missing metric {}", name))
+                .parse()
+                .unwrap()
+        };
+        assert_eq!(value("phone_lookup_queries_total"), 2);
+        assert_eq!(value("phone_lookup_cache_hits_total"), 1);
+        assert_eq!(value("phone_lookup_cache_misses_total"), 1);
+        assert_eq!(value("phone_lookup_cache_size"), 1);
+        assert!(value("phone_lookup_index_count") > 0);
+        assert!(text.contains("# TYPE phone_lookup_queries_total counter"));
+    }
+
+    #[actix_web::test]
+    async fn test_province_stats_endpoint() {
+        let app = init_app!(test_state());
+        let req = actix_test::TestRequest::get().uri("/stats/provinces").to_request();
+        let body: serde_json::Value = actix_test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["code"], 0);
+        assert!(body["data"]["北京"].as_u64().unwrap() > 0);
+    }
+
+    #[test]
+    fn test_startup_timings_records_phases() {
+        let mut timings = StartupTimings::start();
+        timings.record("config");
+        std::thread::sleep(Duration::from_millis(5));
+        timings.record("database");
+        timings.record("bind");
+
+        let phases: Vec<&str> = timings.phases.iter().map(|(phase, _)| *phase).collect();
+        assert_eq!(phases, vec!["config", "database", "bind"]);
+        assert!(timings.phases[1].1 >= Duration::from_millis(5));
+        assert_eq!(
+            timings.total(),
+            timings.phases.iter().map(|(_, duration)| *duration).sum::<Duration>()
+        );
+
+        let summary = timings.to_string();
+        assert!(summary.starts_with("config="));
+        assert!(summary.contains(" database="));
+        assert!(summary.ends_with("ms"));
+    }
 }
@@ -0,0 +1,75 @@
+//! 以只读内存映射方式加载数据库
+//!
+//! 记录区直接引用映射的文件页，不复制到堆上，适合内存受限的环境加载大型数据库。
+//! 索引仍然解析到内存中，以保证二分查找的性能。
+
+use std::fs::File;
+
+use crate::{ErrorKind, Fallible, PhoneData, RecordBytes};
+
+impl PhoneData {
+    /// 以只读内存映射方式加载 `phone.dat` 格式的数据库，缓存设置与 [`PhoneData::from_file`] 相同
+    ///
+    /// 映射期间数据文件不应被修改或截断，否则读取记录时的行为未定义；
+    /// 更新数据库时应写入新文件再替换（重命名），而不是原地覆盖。
+    pub fn from_file_mmap(path: &str) -> Fallible<PhoneData> {
+        tracing::info!("正在以内存映射方式加载手机号码数据库文件: {}", path);
+        let file = File::open(path)?;
+        // SAFETY: 映射只读，且按上面的约定数据文件在映射期间不会被修改
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+
+        if map.len() < 8 {
+            return Err(ErrorKind::InvalidPhoneDatabase);
+        }
+        let version =
+            String::from_utf8(map[..4].to_vec()).map_err(|_| ErrorKind::InvalidPhoneDatabase)?;
+        let index_offset = Self::four_u8_to_i32(&map[4..8]);
+        if index_offset < 8 {
+            return Err(ErrorKind::InvalidPhoneDatabase);
+        }
+        let index_offset = index_offset as usize;
+        if map.len() < index_offset {
+            return Err(ErrorKind::TruncatedRecords {
+                expected: index_offset - 8,
+                actual: map.len() - 8,
+            });
+        }
+
+        // 与流式加载一致，末尾不足 9 字节的残余被忽略
+        let index = map[index_offset..]
+            .chunks_exact(9)
+            .map(Self::parse_index_item)
+            .collect();
+        let records = RecordBytes::Mapped {
+            map,
+            range: 8..index_offset,
+        };
+        Ok(Self::from_parts(version, records, index, true, 1000))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mmap_matches_regular_load() {
+        let mapped = PhoneData::from_file_mmap("phone.dat").unwrap();
+        let loaded = PhoneData::new().unwrap();
+        assert_eq!(mapped.version(), loaded.version());
+        assert_eq!(mapped.index_count(), loaded.index_count());
+        for no in ["13800138000", "18086834111", "19200000000"] {
+            assert_eq!(mapped.find(no).unwrap(), loaded.find(no).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_mmap_rejects_truncated_file() {
+        let path = std::env::temp_dir().join("phone_lookup_mmap_truncated.dat");
+        let bytes = std::fs::read("phone.dat").unwrap();
+        std::fs::write(&path, &bytes[..1000]).unwrap();
+        let result = PhoneData::from_file_mmap(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(ErrorKind::TruncatedRecords { .. })));
+    }
+}
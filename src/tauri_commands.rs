@@ -9,11 +9,12 @@ use tauri::State;
 #[cfg(feature = "tauri-app")]
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "tauri-app")]
-use crate::{PhoneData, PhoneNoInfo};
+use crate::{config::LoggingConfig, PhoneData, PhoneNoInfo};
 
 /// 批量查询结果结构
 #[cfg(feature = "tauri-app")]
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
 pub struct BatchQueryResult {
     /// 手机号
     pub phone: String,
@@ -28,6 +29,7 @@ pub struct BatchQueryResult {
 /// 应用信息结构
 #[cfg(feature = "tauri-app")]
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
 pub struct AppInfo {
     /// 应用名称
     pub name: String,
@@ -42,6 +44,7 @@ pub struct AppInfo {
 /// 数据库信息
 #[cfg(feature = "tauri-app")]
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
 pub struct DatabaseInfo {
     /// 记录总数
     pub total_records: usize,
@@ -53,11 +56,51 @@ pub struct DatabaseInfo {
     pub total_queries: u64,
 }
 
+/// 缓存统计信息
+#[cfg(feature = "tauri-app")]
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct CacheStatsInfo {
+    /// 当前缓存条目数
+    pub size: usize,
+    /// 最大缓存条目数
+    pub max_size: usize,
+    /// 缓存命中次数
+    pub hits: u64,
+    /// 缓存未命中次数
+    pub misses: u64,
+    /// 总查询次数
+    pub total_queries: u64,
+    /// 缓存命中率（百分比）
+    pub hit_rate: f64,
+}
+
+#[cfg(feature = "tauri-app")]
+impl CacheStatsInfo {
+    fn from_phone_data(data: &PhoneData) -> Self {
+        let stats = data.get_cache_stats();
+        let hit_rate = if stats.total_queries == 0 {
+            0.0
+        } else {
+            stats.hits as f64 / stats.total_queries as f64 * 100.0
+        };
+        CacheStatsInfo {
+            size: stats.size,
+            max_size: stats.max_size,
+            hits: stats.hits,
+            misses: stats.misses,
+            total_queries: stats.total_queries,
+            hit_rate,
+        }
+    }
+}
+
 /// 单个手机号查询命令
 /// 
 /// # 参数
 /// - phone: 手机号字符串
 /// - data: PhoneData 状态
+/// - logging: 日志配置，`mask_phone` 决定日志中的号码是否脱敏
 /// 
 /// # 返回
 /// 成功时返回 PhoneNoInfo，失败时返回错误字符串
@@ -65,20 +108,22 @@ pub struct DatabaseInfo {
 #[tauri::command]
 pub async fn query_phone(
     phone: String,
-    data: State<'_, Arc<PhoneData>>
+    data: State<'_, Arc<PhoneData>>,
+    logging: State<'_, LoggingConfig>
 ) -> Result<PhoneNoInfo, String> {
-    eprintln!("[DEBUG] 收到查询请求: {}", phone);
-    log::info!("查询手机号: {}", phone);
+    let masked = logging.phone_for_log(&phone);
+    eprintln!("[DEBUG] 收到查询请求: {}", masked);
+    log::info!("查询手机号: {}", masked);
     
     match data.find(&phone) {
         Ok(info) => {
-            eprintln!("[DEBUG] 查询成功: {} -> {:?}", phone, info);
-            log::debug!("查询成功: {} -> {:?}", phone, info);
+            eprintln!("[DEBUG] 查询成功: {} -> {:?}", masked, info);
+            log::debug!("查询成功: {} -> {:?}", masked, info);
             Ok(info)
         }
         Err(e) => {
-            eprintln!("[DEBUG] 查询失败: {} -> {}", phone, e);
-            log::warn!("查询失败: {} -> {}", phone, e);
+            eprintln!("[DEBUG] 查询失败: {} -> {}", masked, e);
+            log::warn!("查询失败: {} -> {}", masked, e);
             Err(format!("查询失败: {}", e))
         }
     }
@@ -98,8 +143,6 @@ pub async fn query_phones_batch(
     phones: Vec<String>,
     data: State<'_, Arc<PhoneData>>
 ) -> Result<Vec<BatchQueryResult>, String> {
-    use futures::stream::{self, StreamExt};
-    
     log::info!("批量查询手机号，数量: {}", phones.len());
     
     if phones.is_empty() {
@@ -110,34 +153,27 @@ pub async fn query_phones_batch(
         return Err("批量查询最多支持100个手机号".to_string());
     }
     
-    let data_ref = data.inner().clone();
-    
-    // 使用 futures::stream::buffered 并发处理，保证结果顺序
-    let results: Vec<BatchQueryResult> = stream::iter(phones.into_iter().enumerate())
-        .map(|(index, phone)| {
-            let data_clone = data_ref.clone();
-            let phone_clone = phone.clone();
-            
-            async move {
-                match data_clone.find(&phone_clone) {
-                    Ok(info) => BatchQueryResult {
-                        phone: phone_clone,
-                        index,
-                        result: Some(info),
-                        error: None,
-                    },
-                    Err(e) => BatchQueryResult {
-                        phone: phone_clone,
-                        index,
-                        result: None,
-                        error: Some(e.to_string()),
-                    },
-                }
-            }
+    // 库层批量查询保证结果与输入顺序一一对应
+    let numbers: Vec<&str> = phones.iter().map(String::as_str).collect();
+    let results: Vec<BatchQueryResult> = data
+        .find_batch_map(&numbers)
+        .into_iter()
+        .enumerate()
+        .map(|(index, (phone, result))| match result {
+            Ok(info) => BatchQueryResult {
+                phone,
+                index,
+                result: Some(info),
+                error: None,
+            },
+            Err(e) => BatchQueryResult {
+                phone,
+                index,
+                result: None,
+                error: Some(e.to_string()),
+            },
         })
-        .buffered(100) // 最多100个并发查询
-        .collect()
-        .await;
+        .collect();
     
     log::info!("批量查询完成，处理数量: {}", results.len());
     Ok(results)
@@ -171,6 +207,16 @@ pub async fn get_app_info(
     Ok(app_info)
 }
 
+/// 获取缓存统计命令，供前端统计面板实时展示命中率
+#[cfg(feature = "tauri-app")]
+#[tauri::command]
+pub async fn get_cache_stats(
+    data: State<'_, Arc<PhoneData>>
+) -> Result<CacheStatsInfo, String> {
+    log::debug!("获取缓存统计");
+    Ok(CacheStatsInfo::from_phone_data(&data))
+}
+
 /// 清空缓存命令
 #[cfg(feature = "tauri-app")]
 #[tauri::command]
@@ -220,4 +266,22 @@ mod tests {
         // 测试空数组的批量查询
         // 这个测试不需要实际的数据库连接
     }
+
+    #[test]
+    fn test_get_cache_stats_command() {
+        // 命令由 tauri::command 宏展开，能在这里引用即说明签名可被 generate_handler 注册
+        let _command = get_cache_stats;
+
+        let data = PhoneData::new().unwrap();
+        let stats = CacheStatsInfo::from_phone_data(&data);
+        assert_eq!(stats.total_queries, 0);
+        assert_eq!(stats.hit_rate, 0.0);
+
+        data.find("13800138000").unwrap();
+        data.find("13800138000").unwrap();
+        let stats = CacheStatsInfo::from_phone_data(&data);
+        assert_eq!(stats.size, 1);
+        assert_eq!((stats.hits, stats.misses, stats.total_queries), (1, 1, 2));
+        assert_eq!(stats.hit_rate, 50.0);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,93 @@
+//! `find` 与 `find_ref` 的内存分配次数对比
+//!
+//! 运行 `cargo test --release --test alloc_test -- --nocapture` 查看每次查询的分配次数。
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use phone_lookup_rs::PhoneData;
+
+/// 按线程统计分配次数的分配器，避免并行运行的其他测试干扰计数
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// 统计 `f` 执行期间当前线程的分配次数
+fn count_allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+const PHONES: [&str; 4] = ["13800138000", "18086834111", "15900000000", "19200000000"];
+const ROUNDS: usize = 1000;
+
+#[test]
+fn test_find_ref_does_not_allocate() {
+    let cached = PhoneData::new().expect("Failed to load phone data");
+    let uncached = PhoneData::builder()
+        .cache_enabled(false)
+        .build("phone.dat")
+        .expect("Failed to load phone data");
+    // 预热记录缓存等首次查询才构建的结构
+    for phone in PHONES {
+        cached.find(phone).unwrap();
+        uncached.find(phone).unwrap();
+        cached.find_ref(phone).unwrap();
+    }
+
+    let queries = PHONES.len() * ROUNDS;
+    let run = |f: &dyn Fn(&str)| {
+        count_allocations(|| {
+            for _ in 0..ROUNDS {
+                for phone in PHONES {
+                    f(phone);
+                }
+            }
+        })
+    };
+    let find_cached = run(&|phone| {
+        std::hint::black_box(cached.find(phone).unwrap());
+    });
+    let find_uncached = run(&|phone| {
+        std::hint::black_box(uncached.find(phone).unwrap());
+    });
+    let find_ref = run(&|phone| {
+        let info = cached.find_ref(phone).unwrap();
+        std::hint::black_box((info.province(), info.city(), info.card_type()));
+    });
+
+    println!("每次查询的平均分配次数（{} 次查询）:", queries);
+    println!(
+        "  find（缓存命中）: {:.2}",
+        find_cached as f64 / queries as f64
+    );
+    println!(
+        "  find（禁用缓存）: {:.2}",
+        find_uncached as f64 / queries as f64
+    );
+    println!(
+        "  find_ref:         {:.2}",
+        find_ref as f64 / queries as f64
+    );
+
+    assert!(find_cached >= queries);
+    assert!(find_uncached >= queries);
+    assert_eq!(find_ref, 0);
+}
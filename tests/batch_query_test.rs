@@ -49,13 +49,15 @@ async fn batch_query_logic(
                     data: None,
                     error: Some("手机号码未找到".to_string()),
                 },
-                Err(ErrorKind::InvalidLength) => BatchQueryResult {
-                    phone: phone_clone,
-                    index,
-                    success: false,
-                    data: None,
-                    error: Some("手机号码格式无效".to_string()),
-                },
+                Err(ErrorKind::InvalidLength | ErrorKind::InvalidCharacter { .. }) => {
+                    BatchQueryResult {
+                        phone: phone_clone,
+                        index,
+                        success: false,
+                        data: None,
+                        error: Some("手机号码格式无效".to_string()),
+                    }
+                }
                 Err(ErrorKind::InvalidPhoneDatabase) => BatchQueryResult {
                     phone: phone_clone,
                     index,
@@ -257,9 +259,9 @@ async fn test_batch_query_index_mapping() {
     // 验证无效格式的处理
     let invalid_result = &results[1];
     assert!(!invalid_result.success);
-    // "invalid" 会被解析失败，触发数据库格式错误
+    // "invalid" 含有非数字字符，属于输入错误而不是数据库格式错误
     let error_msg = invalid_result.error.as_ref().unwrap();
-    assert!(error_msg.contains("数据库格式错误") || error_msg.contains("格式无效"));
+    assert!(error_msg.contains("格式无效"));
 }
 
 #[cfg(test)]
@@ -410,6 +412,46 @@ mod performance_tests {
                 * 100.0
         );
     }
+
+    /// 对比 buffered 方案与 spawn_blocking 分块方案在大批量下的耗时
+    #[cfg(feature = "async")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_buffered_vs_spawn_blocking_performance() {
+        // 关闭缓存，避免先执行的方案为后者预热缓存
+        let phone_data = Arc::new(
+            PhoneData::builder()
+                .cache_enabled(false)
+                .build("phone.dat")
+                .expect("Failed to load phone data"),
+        );
+
+        let phones: Vec<String> = (0..100_000u64)
+            .map(|i| format!("1{}", 3_000_000_000 + i * 59_997))
+            .collect();
+
+        let start = Instant::now();
+        let (buffered_results, _) = batch_query_logic(phone_data.clone(), phones.clone()).await;
+        let buffered_duration = start.elapsed();
+
+        let start = Instant::now();
+        let blocking_results = phone_data.find_batch_async(phones).await;
+        let blocking_duration = start.elapsed();
+
+        assert_eq!(buffered_results.len(), blocking_results.len());
+        for (buffered, blocking) in buffered_results.iter().zip(&blocking_results) {
+            assert_eq!(buffered.success, blocking.is_ok());
+        }
+
+        println!("buffered 批量查询100000个号码耗时: {:?}", buffered_duration);
+        println!(
+            "spawn_blocking 批量查询100000个号码耗时: {:?}",
+            blocking_duration
+        );
+        println!(
+            "spawn_blocking 相对于 buffered 的加速比: {:.2}x",
+            buffered_duration.as_secs_f64() / blocking_duration.as_secs_f64()
+        );
+    }
 }
 
 #[cfg(test)]
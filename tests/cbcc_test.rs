@@ -0,0 +1,87 @@
+//! 中国广电（192 号段）查询正确性测试
+
+use phone_lookup_rs::{CardType, ErrorKind, Locale, PhoneData};
+
+/// 按 `phone.dat` 格式构造只含给定号段的小数据库，条目须按号段升序排列
+fn build_phone_dat(entries: &[(i32, &str, u8)]) -> Vec<u8> {
+    let mut records = Vec::new();
+    let mut offsets = Vec::new();
+    for (_, record, _) in entries {
+        offsets.push(8 + records.len() as i32);
+        records.extend_from_slice(record.as_bytes());
+        records.push(0);
+    }
+
+    let mut bytes = b"2502".to_vec();
+    bytes.extend_from_slice(&(8 + records.len() as i32).to_le_bytes());
+    bytes.extend_from_slice(&records);
+    for ((prefix, _, card_type), offset) in entries.iter().zip(offsets) {
+        bytes.extend_from_slice(&prefix.to_le_bytes());
+        bytes.extend_from_slice(&offset.to_le_bytes());
+        bytes.push(*card_type);
+    }
+    bytes
+}
+
+#[test]
+fn test_bundled_cbcc_segments() {
+    let phone_data = PhoneData::new().expect("Failed to load phone data");
+
+    // 192 号段的首尾两端
+    for phone in ["19200000000", "19299990000"] {
+        let info = phone_data.find(phone).unwrap();
+        assert_eq!(info.card_type, "中国广电", "{}", phone);
+        assert_eq!(info.icon_key, "cbcc");
+        assert!(!info.province.is_empty());
+        assert_eq!(info.carrier(), Some(CardType::Cbcc));
+
+        let typed = phone_data.find_typed(phone).unwrap();
+        assert_eq!(typed.carrier, CardType::Cbcc);
+        assert!(!typed.is_virtual);
+    }
+
+    // 紧邻 192 号段之前的号段属于其他运营商
+    let before = phone_data.find("19199890000").unwrap();
+    assert_ne!(before.card_type, "中国广电");
+
+    let info = phone_data
+        .find_localized("19200000000", Locale::En)
+        .unwrap();
+    assert_eq!(info.card_type, "China Broadnet");
+}
+
+#[test]
+fn test_in_memory_cbcc_database() {
+    let bytes = build_phone_dat(&[
+        (1380013, "北京|北京|100000|010", 1),
+        (1920000, "北京|北京|100000|010", 7),
+        (1920755, "广东|深圳|518000|0755", 7),
+        (1929999, "新疆|乌鲁木齐|830000|0991", 8),
+    ]);
+    let phone_data = PhoneData::from_bytes(&bytes).unwrap();
+    assert_eq!(phone_data.index_count(), 4);
+
+    let info = phone_data.find("19207550000").unwrap();
+    assert_eq!(info.city, "深圳");
+    assert_eq!(info.card_type, "中国广电");
+
+    // 最后一个索引项为广电虚拟运营商
+    let info = phone_data.find("19299991234").unwrap();
+    assert_eq!(info.city, "乌鲁木齐");
+    assert_eq!(info.card_type, "中国广电虚拟运营商");
+    assert_eq!(info.icon_key, "cbcc_v");
+    let typed = phone_data.find_typed("19299991234").unwrap();
+    assert_eq!(typed.carrier, CardType::CbccV);
+    assert!(typed.is_virtual);
+    assert_eq!(typed.carrier.network(), CardType::Cbcc);
+
+    // 号段之间的空隙未找到，而不是命中相邻的广电记录
+    assert!(matches!(
+        phone_data.find("19200010000"),
+        Err(ErrorKind::NotFound)
+    ));
+    assert!(matches!(
+        phone_data.find("19300000000"),
+        Err(ErrorKind::NotFound)
+    ));
+}
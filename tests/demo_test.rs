@@ -57,6 +57,44 @@ fn test_multiple_phone_lookups() {
     }
 }
 
+#[test]
+fn test_shared_instance_is_reused() {
+    let first = PhoneData::shared("phone.dat").expect("Failed to load phone data");
+    let second = PhoneData::shared("./phone.dat").expect("Failed to load phone data");
+    assert!(std::sync::Arc::ptr_eq(&first, &second));
+
+    let result = PhoneData::shared("missing-phone.dat");
+    assert!(matches!(result, Err(ErrorKind::Io(_))));
+}
+
+#[test]
+fn test_from_file_verified_bundled_database() {
+    let (phone_data, report) =
+        PhoneData::from_file_verified("phone.dat", true, 1000).expect("Failed to load phone data");
+
+    assert_eq!(report.checked, phone_data.index_count());
+    assert_eq!(report.offset_out_of_range, 0);
+    assert_eq!(report.malformed_records, 0);
+    assert_eq!(report.unordered_prefixes, 0);
+    // 内置数据中少量号段使用 0 作为运营商代码，按未知运营商处理，不算作问题
+    assert_eq!(report.unknown_card_types, phone_data.unknown_card_types().len());
+    assert!(report.is_clean());
+    assert!(phone_data.find("13800138000").is_ok());
+}
+
+#[test]
+fn test_subset_of_bundled_database() {
+    let phone_data = PhoneData::new().expect("Failed to load phone data");
+    let subset = phone_data
+        .subset_by_province("北京")
+        .expect("Failed to build subset");
+
+    assert!(subset.verify_integrity().is_clean());
+    assert!(subset.index_count() < phone_data.index_count());
+    assert_eq!(subset.find("13800138000").unwrap().province, "北京");
+    assert!(subset.find("13900000000").is_err());
+}
+
 #[cfg(test)]
 mod performance_tests {
     use super::*;